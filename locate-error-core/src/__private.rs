@@ -0,0 +1,371 @@
+//! Support code for `#[derive(Locate)]`, not public API.
+//!
+//! Uses autoref specialization so the generated code can opt into behaviour depending on the
+//! traits the user's types implement, without adding bounds to the generated impls.
+
+use crate::runtime::{self, Mode};
+use crate::{Located, Location, register};
+use core::cell::Cell;
+use core::error::Error;
+use core::fmt::{Debug, Display};
+use core::marker::PhantomData;
+#[cfg(not(locate_error_strip))]
+use core::panic;
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::process::{ExitCode, Termination};
+
+/// The location passed from the caller, nothing when locations are stripped so no file paths are
+/// compiled into the binary
+#[cfg(not(locate_error_strip))]
+pub type Caller = &'static panic::Location<'static>;
+#[cfg(locate_error_strip)]
+pub type Caller = ();
+
+#[cfg(not(locate_error_strip))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __caller {
+    () => {
+        ::core::panic::Location::caller()
+    };
+}
+
+#[cfg(locate_error_strip)]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __caller {
+    () => {
+        ()
+    };
+}
+
+/// Marks the wrapped function `#[track_caller]` unless locations are stripped
+#[cfg(not(locate_error_strip))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __track_caller {
+    ($($function:tt)*) => {
+        #[track_caller]
+        $($function)*
+    };
+}
+
+#[cfg(locate_error_strip)]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __track_caller {
+    ($($function:tt)*) => {
+        $($function)*
+    };
+}
+
+pub use crate::{__caller as caller, __track_caller as track_caller};
+
+/// Called by the generated `From` impls to capture the location of the conversion.
+///
+/// `first_hop` is set when the converted value does not already carry a location. `message` and
+/// `error` are the converted value, when it implements `Display` or `Error`. Conversions are not
+/// counted or reported to the hook while locations are turned off at runtime.
+pub fn locate<S, T>(
+    caller: Caller,
+    first_hop: bool,
+    message: Option<&dyn Display>,
+    error: Option<&(dyn Error + 'static)>,
+) -> Location {
+    if runtime::mode() == Mode::Off {
+        return Location::from_parts(String::new(), 0, 0);
+    }
+    #[cfg(feature = "metrics")]
+    crate::metrics::conversion(caller, core::any::type_name::<T>());
+    let location = capture::<S, T>(caller, first_hop, message);
+    crate::hook::conversion::<S, T>(&location, first_hop, error, message);
+    location
+}
+
+// The type names and message are only used by the `tracing` feature
+#[allow(unused_variables, clippy::extra_unused_type_parameters)]
+fn capture<S, T>(caller: Caller, first_hop: bool, message: Option<&dyn Display>) -> Location {
+    #[allow(unused_mut)]
+    let mut location = Location::from_caller(caller);
+    #[cfg(all(feature = "spantrace", not(locate_error_strip)))]
+    if first_hop {
+        location.span_trace = Some(tracing_error::SpanTrace::capture());
+    }
+    #[cfg(all(feature = "backtrace", not(locate_error_strip)))]
+    if first_hop {
+        location.backtrace = Some(std::sync::Arc::new(std::backtrace::Backtrace::capture()));
+    }
+    #[cfg(feature = "tracing")]
+    crate::tracing::conversion(
+        &location,
+        core::any::type_name::<S>(),
+        core::any::type_name::<T>(),
+        message,
+    );
+    location
+}
+
+/// Called by `location!` to capture the location of the caller
+pub fn location(caller: Caller) -> Location {
+    if runtime::mode() == Mode::Off {
+        return Location::from_parts(String::new(), 0, 0);
+    }
+    let location = Location::from_caller(caller);
+    #[cfg(feature = "tracing")]
+    crate::tracing::location(&location);
+    location
+}
+
+/// Called by `permalinks!` with the build environment of the caller's crate, where the repository
+/// was normalised by `permalink::emit_build_env`
+pub fn permalinks(
+    repository: Option<&str>,
+    commit: Option<&str>,
+    repository_root: Option<&str>,
+) -> Option<crate::permalink::Permalinks> {
+    let repository = repository.filter(|r| !r.is_empty())?;
+    let commit = commit.filter(|c| !c.is_empty())?;
+    let permalinks = crate::permalink::Permalinks::new(repository, commit);
+    Some(match repository_root {
+        Some(root) => permalinks.repository_root(root),
+        None => permalinks,
+    })
+}
+
+/// Called by `#[locate_error::main]` to run a `main` returning a `Result`, printing an error with
+/// `print_error` and exiting with `exit_code`
+pub fn run_main<T, E>(
+    main: impl FnOnce() -> Result<T, E>,
+    exit_code: u8,
+    print_error: impl FnOnce(E),
+) -> ExitCode
+where
+    T: Termination,
+{
+    crate::panic::install_hook();
+    match main() {
+        Ok(value) => value.report(),
+        Err(error) => {
+            print_error(error);
+            ExitCode::from(exit_code)
+        }
+    }
+}
+
+/// Wraps the error returned from `main` to print it as a located report when possible, called as
+/// `(&&&MainError::new(error)).print_error(caller!())` with the location `main` returned it from
+pub struct MainError<E>(Cell<Option<E>>);
+
+impl<E> MainError<E> {
+    pub fn new(error: E) -> Self {
+        MainError(Cell::new(Some(error)))
+    }
+
+    /// The error, which is only printed once
+    fn take(&self) -> E {
+        self.0
+            .take()
+            .expect("the error returned from `main` is printed once")
+    }
+}
+
+pub trait PrintError {
+    fn print_error(&self, caller: Caller);
+}
+
+impl<E: Error + 'static> PrintError for &&MainError<E> {
+    fn print_error(&self, caller: Caller) {
+        crate::report::eprint_main_error::<E>(Box::new(self.take()), caller);
+    }
+}
+
+/// Boxed errors, which don't implement `Error` themselves
+pub trait PrintBoxedError {
+    fn print_error(&self, caller: Caller);
+}
+
+impl<E: BoxedError + ?Sized> PrintBoxedError for &MainError<Box<E>> {
+    fn print_error(&self, caller: Caller) {
+        crate::report::eprint_main_error::<Box<E>>(self.take().into_error(), caller);
+    }
+}
+
+/// Other errors are printed with `Debug`, as by the standard library
+pub trait PrintDebugError {
+    fn print_error(&self, caller: Caller);
+}
+
+impl<E: Debug> PrintDebugError for MainError<E> {
+    fn print_error(&self, _: Caller) {
+        eprintln!("Error: {:?}", self.take());
+    }
+}
+
+/// The error types which may be boxed as `Box<dyn Error>`
+pub trait BoxedError {
+    fn as_error(&self) -> &(dyn Error + 'static);
+
+    fn into_error(self: Box<Self>) -> Box<dyn Error + 'static>;
+}
+
+impl BoxedError for dyn Error + 'static {
+    fn as_error(&self) -> &(dyn Error + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn Error + 'static> {
+        self
+    }
+}
+
+impl BoxedError for dyn Error + Send + 'static {
+    fn as_error(&self) -> &(dyn Error + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn Error + 'static> {
+        self
+    }
+}
+
+impl BoxedError for dyn Error + Send + Sync + 'static {
+    fn as_error(&self) -> &(dyn Error + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn Error + 'static> {
+        self
+    }
+}
+
+/// Wraps a converted value to expose it as `dyn Display` when possible
+pub struct Message<'a, T>(pub &'a T);
+
+pub trait DisplayMessage<'a> {
+    fn message(&self) -> Option<&'a dyn Display>;
+}
+
+impl<'a, T: Display> DisplayMessage<'a> for Message<'a, T> {
+    fn message(&self) -> Option<&'a dyn Display> {
+        Some(self.0)
+    }
+}
+
+pub trait NoDisplayMessage<'a> {
+    fn message(&self) -> Option<&'a dyn Display> {
+        None
+    }
+}
+
+impl<'a, T> NoDisplayMessage<'a> for &Message<'a, T> {}
+
+/// Wraps a `#[locate_from]` field to expose it as a `dyn Error` when possible
+pub struct Source<'a, T>(pub &'a T);
+
+pub trait ErrorSource<'a> {
+    fn located_source(&self) -> Option<&'a (dyn Error + 'static)>;
+}
+
+impl<'a, T: Error + 'static> ErrorSource<'a> for Source<'a, T> {
+    fn located_source(&self) -> Option<&'a (dyn Error + 'static)> {
+        Some(self.0)
+    }
+}
+
+pub trait NoErrorSource<'a> {
+    fn located_source(&self) -> Option<&'a (dyn Error + 'static)> {
+        None
+    }
+}
+
+impl<'a, T> NoErrorSource<'a> for &Source<'a, T> {}
+
+pub trait LocatedSource {
+    /// Whether the value already carries the location of an earlier conversion
+    fn is_located(&self) -> bool;
+}
+
+impl<T: Located> LocatedSource for Source<'_, T> {
+    fn is_located(&self) -> bool {
+        self.0.location().is_some()
+    }
+}
+
+pub trait NotLocatedSource {
+    fn is_located(&self) -> bool {
+        false
+    }
+}
+
+impl<T> NotLocatedSource for &Source<'_, T> {}
+
+/// Registers the target of a located conversion when it is a `'static` error
+pub struct Register<T>(PhantomData<T>);
+
+impl<T> Register<T> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Register(PhantomData)
+    }
+}
+
+pub trait RegisterError {
+    /// Registers the target, only on the first call when given a flag for the target type
+    fn register(&self, registered: Option<&'static AtomicBool>);
+}
+
+impl<T: Located + Error + 'static> RegisterError for Register<T> {
+    fn register(&self, registered: Option<&'static AtomicBool>) {
+        match registered {
+            Some(registered) if registered.load(Ordering::Relaxed) => {}
+            Some(registered) => {
+                register::<T>();
+                registered.store(true, Ordering::Relaxed);
+            }
+            None => register::<T>(),
+        }
+    }
+}
+
+pub trait NoRegisterError {
+    fn register(&self, _registered: Option<&'static AtomicBool>) {}
+}
+
+impl<T> NoRegisterError for &Register<T> {}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[cfg(all(feature = "backtrace", not(locate_error_strip)))]
+    #[test]
+    fn test_backtrace() {
+        let location = locate::<(), ()>(panic::Location::caller(), true, None, None);
+        assert!(location.backtrace().is_some());
+
+        // Only the first hop of a chain captures a backtrace
+        let location = locate::<(), ()>(panic::Location::caller(), false, None, None);
+        assert!(location.backtrace().is_none());
+    }
+
+    #[cfg(all(feature = "spantrace", not(locate_error_strip)))]
+    #[test]
+    fn test_span_trace() {
+        use tracing_error::{ErrorLayer, SpanTraceStatus};
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let subscriber = tracing_subscriber::registry().with(ErrorLayer::default());
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = tracing::info_span!("request", id = 7).entered();
+            let location = locate::<(), ()>(panic::Location::caller(), true, None, None);
+            let span_trace = location.span_trace().unwrap();
+            assert_eq!(span_trace.status(), SpanTraceStatus::CAPTURED);
+            assert!(span_trace.to_string().contains("request"));
+
+            // Only the first hop of a chain captures a span trace
+            let location = locate::<(), ()>(panic::Location::caller(), false, None, None);
+            assert!(location.span_trace().is_none());
+        });
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{Located, Location, as_located};
+use core::error::Error;
+
+/// A single error in a [`Chain`]
+#[derive(Clone, Copy)]
+pub struct Hop<'a> {
+    error: &'a (dyn Error + 'static),
+    located: Option<&'a dyn Located>,
+}
+
+impl<'a> Hop<'a> {
+    /// The error at this hop
+    pub fn error(&self) -> &'a (dyn Error + 'static) {
+        self.error
+    }
+
+    /// Location captured when this error was created by a located conversion
+    pub fn location(&self) -> Option<&'a Location> {
+        self.located.and_then(|located| located.location())
+    }
+
+    /// Type name of the error, known only for registered `Located` types
+    pub fn type_name(&self) -> Option<&'static str> {
+        self.located.map(|located| located.type_name())
+    }
+}
+
+/// Iterator over an error and its sources.
+///
+/// For registered `Located` errors the `#[locate_from]` value is followed, otherwise
+/// `Error::source` is used. Generic types are only registered by calling
+/// [`register`](crate::register), see [`Located`].
+#[derive(Clone)]
+pub struct Chain<'a> {
+    next: Option<&'a (dyn Error + 'static)>,
+}
+
+impl<'a> Chain<'a> {
+    pub fn new(error: &'a (dyn Error + 'static)) -> Self {
+        Chain { next: Some(error) }
+    }
+}
+
+impl<'a> Iterator for Chain<'a> {
+    type Item = Hop<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let error = self.next?;
+        let located = as_located(error);
+        self.next = located
+            .and_then(|located| located.located_source())
+            .or_else(|| error.source());
+        Some(Hop { error, located })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{Inner, Outer};
+    use crate::location;
+
+    /// Never registered, unlike the fixtures
+    #[derive(Debug)]
+    struct Unregistered {
+        inner: Inner,
+        location: Location,
+    }
+
+    impl core::fmt::Display for Unregistered {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            write!(f, "unregistered")
+        }
+    }
+
+    impl Error for Unregistered {}
+
+    impl Located for Unregistered {
+        fn location(&self) -> Option<&Location> {
+            Some(&self.location)
+        }
+
+        fn located_source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.inner)
+        }
+    }
+
+    #[test]
+    fn test_unregistered() {
+        let unregistered = Unregistered {
+            inner: Inner,
+            location: location!(),
+        };
+        // Unregistered types are treated as plain errors
+        let hops: Vec<_> = Chain::new(&unregistered).collect();
+        assert_eq!(hops.len(), 1);
+        assert!(hops[0].location().is_none());
+        assert!(hops[0].type_name().is_none());
+    }
+
+    #[test]
+    fn test_chain() {
+        let outer = Outer::new();
+        let hops: Vec<_> = Chain::new(&outer).collect();
+        assert_eq!(hops.len(), 2);
+        assert_eq!(hops[0].error().to_string(), "outer");
+        assert_eq!(hops[0].location().unwrap(), &outer.location);
+        assert!(hops[0].type_name().unwrap().ends_with("Outer"));
+        assert_eq!(hops[1].error().to_string(), "inner");
+        assert!(hops[1].location().is_none());
+        assert!(hops[1].type_name().is_none());
+    }
+}
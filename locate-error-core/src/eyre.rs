@@ -0,0 +1,93 @@
+//! An [`eyre::EyreHandler`] which reports the locations captured by `#[derive(Locate)]` errors
+
+use crate::render::Renderer;
+use core::error::Error;
+use core::fmt;
+
+/// Report handler printing each error in the chain with its location.
+///
+/// ```text
+/// Error: Outer message
+///     at src/main.rs:28:61
+///
+/// Caused by:
+///    0: Inner message
+///       at src/main.rs:40:19
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct LocatedHandler {
+    renderer: Renderer,
+}
+
+impl LocatedHandler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renders reports with a configured [`Renderer`]
+    pub fn with_renderer(renderer: Renderer) -> Self {
+        LocatedHandler { renderer }
+    }
+}
+
+impl eyre::EyreHandler for LocatedHandler {
+    fn debug(&self, error: &(dyn Error + 'static), f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return fmt::Debug::fmt(error, f);
+        }
+        fmt::Display::fmt(&self.renderer.render(error), f)
+    }
+}
+
+/// Installs [`LocatedHandler`] as the global `eyre` report handler
+pub fn install() -> Result<(), eyre::InstallError> {
+    install_handler(LocatedHandler::new())
+}
+
+/// Installs a configured [`LocatedHandler`] as the global `eyre` report handler
+pub fn install_handler(handler: LocatedHandler) -> Result<(), eyre::InstallError> {
+    eyre::set_hook(Box::new(move |_| Box::new(handler.clone())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Location;
+    use crate::fixtures::Outer;
+    use crate::paths::PathRemap;
+
+    #[test]
+    fn test_handler() {
+        install().unwrap();
+        let outer = Outer::new();
+        let expected = format!(
+            "outer\n    at {}\n\nCaused by:\n   0: inner",
+            outer.location
+        );
+        let report = eyre::Report::new(outer);
+        assert_eq!(format!("{report:?}"), expected);
+    }
+
+    #[cfg(not(locate_error_strip))]
+    #[test]
+    fn test_handler_paths() {
+        let location = Location::from_parts("/build/src/main.rs".to_string(), 1, 2);
+        let renderer = Renderer::new().paths(PathRemap::new().strip_prefix("/build/"));
+        let handler = LocatedHandler::with_renderer(renderer);
+        let outer = Outer::at(location);
+        let rendered = format!("{:?}", Debug(&handler, &outer));
+        assert_eq!(
+            rendered,
+            "outer\n    at src/main.rs:1:2\n\nCaused by:\n   0: inner"
+        );
+    }
+
+    /// Formats an error with a handler which is not installed globally
+    struct Debug<'a>(&'a LocatedHandler, &'a (dyn Error + 'static));
+
+    impl fmt::Debug for Debug<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            eyre::EyreHandler::debug(self.0, self.1, f)
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! Stable fingerprints of located error chains, for grouping occurrences of the same failure.
+//!
+//! By default a fingerprint covers the type name, file and line of each error in the chain, so it
+//! is unaffected by dynamic message contents and by edits which only shift code within a line.
+//!
+//! # Algorithm
+//!
+//! The fingerprint is the 64 bit FNV-1a hash of the following bytes, for each error in the chain
+//! from the outermost to the innermost:
+//!
+//! 1. unless turned off with [`Fingerprinter::type_names`], the UTF-8 type name, empty when
+//!    unknown, followed by a `0` byte
+//! 2. the UTF-8 file, followed by a `0` byte
+//! 3. the line in decimal ASCII, followed by a `0` byte
+//! 4. with [`Fingerprinter::columns`], the column in decimal ASCII, followed by a `0` byte
+//! 5. with [`Fingerprinter::messages`], the UTF-8 message, followed by a `0` byte
+//!
+//! Errors without a location, or with locations turned off at runtime, use an empty file and line
+//! and column `0`. Files are hashed as exported in a [`Snapshot`]: remapped by the global
+//! [`PathRemap`](crate::paths::PathRemap), or replaced by their
+//! [`hash_path`](crate::runtime::hash_path) when locations are redacted. The algorithm does not
+//! depend on the platform and will not change between versions of this crate.
+//!
+//! Captured paths are absolute for code compiled outside the workspace, such as dependencies, so
+//! fingerprints of errors located there differ across build machines unless a `PathRemap` making
+//! them relative is set with [`paths::set_global`](crate::paths::set_global).
+//!
+//! Type names come from [`core::any::type_name`], whose output is not guaranteed to be the same
+//! across compiler versions, so fingerprints including them may change when upgrading the
+//! compiler. Fingerprints without type names only depend on the locations.
+
+use crate::{Chain, Location, Snapshot, paths};
+use core::error::Error;
+use core::fmt;
+
+/// A fingerprint of an error chain, displayed as 16 lowercase hex digits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fingerprint(u64);
+
+impl Fingerprint {
+    pub fn value(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// Options for computing a [`Fingerprint`]
+#[derive(Debug, Clone, Copy)]
+pub struct Fingerprinter {
+    type_names: bool,
+    columns: bool,
+    messages: bool,
+}
+
+impl Default for Fingerprinter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Fingerprinter {
+    /// Fingerprints the type name, file and line of each error
+    pub fn new() -> Self {
+        Fingerprinter {
+            type_names: true,
+            columns: false,
+            messages: false,
+        }
+    }
+
+    /// Whether to fingerprint the type name of each error, which may change between compiler
+    /// versions. Without type names, errors are identified by their file and line only.
+    pub fn type_names(mut self, type_names: bool) -> Self {
+        self.type_names = type_names;
+        self
+    }
+
+    /// Also fingerprints the column of each location
+    pub fn columns(mut self, columns: bool) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// Also fingerprints the message of each error
+    pub fn messages(mut self, messages: bool) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    /// Fingerprints an error and its sources, walked as by [`Chain`]
+    pub fn error(&self, error: &(dyn Error + 'static)) -> Fingerprint {
+        let mut hash = Fnv1a::new();
+        for hop in Chain::new(error) {
+            let message = self.messages.then(|| hop.error().to_string());
+            let location = hop.location().and_then(paths::export_location);
+            self.write_hop(
+                &mut hash,
+                hop.type_name(),
+                location.as_ref(),
+                message.as_deref(),
+            );
+        }
+        Fingerprint(hash.0)
+    }
+
+    /// Fingerprints a snapshot, giving the same fingerprint as the error it was taken from
+    pub fn snapshot(&self, snapshot: &Snapshot) -> Fingerprint {
+        let mut hash = Fnv1a::new();
+        for hop in &snapshot.hops {
+            self.write_hop(
+                &mut hash,
+                hop.type_name.as_deref(),
+                hop.location.as_ref(),
+                Some(&hop.message),
+            );
+        }
+        Fingerprint(hash.0)
+    }
+
+    fn write_hop(
+        &self,
+        hash: &mut Fnv1a,
+        type_name: Option<&str>,
+        location: Option<&Location>,
+        message: Option<&str>,
+    ) {
+        let (file, line, column) = location
+            .map(|location| (location.file(), location.line(), location.column()))
+            .unwrap_or_default();
+        if self.type_names {
+            hash.field(type_name.unwrap_or_default().as_bytes());
+        }
+        hash.field(file.as_bytes());
+        hash.field(line.to_string().as_bytes());
+        if self.columns {
+            hash.field(column.to_string().as_bytes());
+        }
+        if self.messages {
+            hash.field(message.unwrap_or_default().as_bytes());
+        }
+    }
+}
+
+impl Snapshot {
+    /// Fingerprints the type name, file and line of each error, see [`Fingerprinter`] for other
+    /// options
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprinter::new().snapshot(self)
+    }
+}
+
+/// 64 bit FNV-1a
+pub(crate) struct Fnv1a(pub(crate) u64);
+
+impl Fnv1a {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    pub(crate) fn new() -> Self {
+        Fnv1a(Self::OFFSET_BASIS)
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(Self::PRIME);
+        }
+    }
+
+    /// Writes the bytes followed by a `0` byte
+    fn field(&mut self, bytes: &[u8]) {
+        self.write(bytes);
+        self.write(&[0]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SnapshotHop;
+    use crate::fixtures::Outer;
+
+    fn hop(message: &str, type_name: Option<&str>, location: Option<Location>) -> SnapshotHop {
+        SnapshotHop {
+            message: message.to_string(),
+            type_name: type_name.map(str::to_string),
+            location,
+            span_trace: None,
+            backtrace: None,
+        }
+    }
+
+    fn snapshot(user: &str, column: u32) -> Snapshot {
+        Snapshot {
+            hops: vec![
+                hop(
+                    &format!("no account for {user}"),
+                    Some("app::Error"),
+                    Some(Location::from_parts("src/main.rs".to_string(), 40, column)),
+                ),
+                hop(&format!("{user} not found"), None, None),
+            ],
+        }
+    }
+
+    #[cfg(not(locate_error_strip))]
+    #[test]
+    fn test_stable() {
+        // Changing this value breaks the grouping of fingerprints stored by users. It is the
+        // FNV-1a hash of these fields, each followed by a 0 byte:
+        // "app::Error", "src/main.rs", "40", "", "", "0"
+        assert_eq!(
+            snapshot("alice", 19).fingerprint().to_string(),
+            "332dceac9ed3fcf5"
+        );
+    }
+
+    #[test]
+    fn test_messages() {
+        assert_eq!(
+            snapshot("alice", 19).fingerprint(),
+            snapshot("bob", 19).fingerprint()
+        );
+        let messages = Fingerprinter::new().messages(true);
+        assert_ne!(
+            messages.snapshot(&snapshot("alice", 19)),
+            messages.snapshot(&snapshot("bob", 19))
+        );
+    }
+
+    #[test]
+    fn test_type_names() {
+        let mut renamed = snapshot("alice", 19);
+        renamed.hops[0].type_name = Some("app::v2::Error".to_string());
+        assert_ne!(snapshot("alice", 19).fingerprint(), renamed.fingerprint());
+        let locations = Fingerprinter::new().type_names(false);
+        assert_eq!(
+            locations.snapshot(&snapshot("alice", 19)),
+            locations.snapshot(&renamed)
+        );
+    }
+
+    #[cfg(not(locate_error_strip))]
+    #[test]
+    fn test_columns() {
+        assert_eq!(
+            snapshot("alice", 19).fingerprint(),
+            snapshot("alice", 5).fingerprint()
+        );
+        let columns = Fingerprinter::new().columns(true);
+        assert_ne!(
+            columns.snapshot(&snapshot("alice", 19)),
+            columns.snapshot(&snapshot("alice", 5))
+        );
+    }
+
+    #[cfg(not(locate_error_strip))]
+    #[test]
+    fn test_error() {
+        let outer = Outer::at(Location::from_parts("src/main.rs".to_string(), 40, 19));
+        let snapshot = Snapshot::new(&outer);
+        for fingerprinter in [
+            Fingerprinter::new(),
+            Fingerprinter::new().columns(true).messages(true),
+        ] {
+            assert_eq!(
+                fingerprinter.error(&outer),
+                fingerprinter.snapshot(&snapshot)
+            );
+        }
+        assert_ne!(
+            Fingerprinter::new().error(&outer),
+            Fingerprinter::new().error(&Outer::at(Location::from_parts(
+                "src/main.rs".to_string(),
+                41,
+                19
+            )))
+        );
+    }
+}
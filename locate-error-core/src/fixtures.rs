@@ -0,0 +1,56 @@
+//! Errors shared by the unit tests, implementing `Located` the way `#[derive(Locate)]` would
+
+use crate::{Located, Location, location, register};
+use core::error::Error;
+use core::fmt;
+
+#[derive(Debug)]
+pub struct Inner;
+
+impl fmt::Display for Inner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "inner")
+    }
+}
+
+impl Error for Inner {}
+
+#[derive(Debug)]
+pub struct Outer {
+    pub inner: Inner,
+    pub location: Location,
+}
+
+impl Outer {
+    #[track_caller]
+    pub fn new() -> Self {
+        Self::at(location!())
+    }
+
+    pub fn at(location: Location) -> Self {
+        register::<Outer>();
+        Outer {
+            inner: Inner,
+            location,
+        }
+    }
+}
+
+impl fmt::Display for Outer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "outer")
+    }
+}
+
+// The source is deliberately not exposed through `Error::source`
+impl Error for Outer {}
+
+impl Located for Outer {
+    fn location(&self) -> Option<&Location> {
+        Some(&self.location)
+    }
+
+    fn located_source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.inner)
+    }
+}
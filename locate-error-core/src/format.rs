@@ -0,0 +1,177 @@
+//! Formats for displaying a [`Location`](crate::Location).
+//!
+//! `{}` uses the global format, [`LocationFormat::WithColumn`] by default, and `{:#}` uses the
+//! global alternate format, [`LocationFormat::Rustc`] by default. Both can be changed with
+//! [`set_global`] and [`set_global_alternate`], or overridden for a single location with
+//! [`DisplayLocation::format`](crate::DisplayLocation::format).
+
+use crate::hyperlink::UrlScheme;
+use core::fmt;
+use std::sync::RwLock;
+
+/// How a location is written, with the file already remapped by the active
+/// [`PathRemap`](crate::paths::PathRemap), except for [`LocationFormat::EditorUrl`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LocationFormat {
+    /// `file:line`
+    Compact,
+    /// `file:line:column`
+    WithColumn,
+    /// `--> file:line:column`, as in rustc diagnostics
+    Rustc,
+    /// `vscode://file/path:line:column`, opening the location in VS Code. Built by
+    /// [`UrlScheme::VsCode`] from the original file made absolute, or written as `file:line:column`
+    /// when locations are redacted
+    EditorUrl,
+    /// A template where `{file}`, `{line}` and `{column}` are replaced, e.g. `{file}:{line}`
+    Template(String),
+}
+
+impl LocationFormat {
+    pub fn write(&self, f: &mut dyn fmt::Write, file: &str, line: u32, column: u32) -> fmt::Result {
+        self.write_linked(f, file, Some(file), line, column)
+    }
+
+    /// Writes `file`, or for [`LocationFormat::EditorUrl`] the URL of `original`, falling back to
+    /// `file` without an original to link to
+    pub(crate) fn write_linked(
+        &self,
+        f: &mut dyn fmt::Write,
+        file: &str,
+        original: Option<&str>,
+        line: u32,
+        column: u32,
+    ) -> fmt::Result {
+        match self {
+            LocationFormat::Compact => write!(f, "{file}:{line}"),
+            LocationFormat::WithColumn => write!(f, "{file}:{line}:{column}"),
+            LocationFormat::Rustc => write!(f, "--> {file}:{line}:{column}"),
+            LocationFormat::EditorUrl => match original {
+                Some(original) => UrlScheme::VsCode.write_url(f, original, line, column),
+                None => write!(f, "{file}:{line}:{column}"),
+            },
+            LocationFormat::Template(template) => write_template(f, template, file, line, column),
+        }
+    }
+}
+
+pub(crate) fn write_template(
+    f: &mut dyn fmt::Write,
+    template: &str,
+    file: &str,
+    line: u32,
+    column: u32,
+) -> fmt::Result {
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        f.write_str(&rest[..start])?;
+        rest = &rest[start..];
+        if let Some(after) = rest.strip_prefix("{file}") {
+            f.write_str(file)?;
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("{line}") {
+            write!(f, "{line}")?;
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("{column}") {
+            write!(f, "{column}")?;
+            rest = after;
+        } else {
+            // Not a placeholder, keep the brace
+            f.write_char('{')?;
+            rest = &rest[1..];
+        }
+    }
+    f.write_str(rest)
+}
+
+struct Formats {
+    default: LocationFormat,
+    alternate: LocationFormat,
+}
+
+static GLOBAL: RwLock<Formats> = RwLock::new(Formats {
+    default: LocationFormat::WithColumn,
+    alternate: LocationFormat::Rustc,
+});
+
+/// Sets the format used by `{}`
+pub fn set_global(format: LocationFormat) {
+    GLOBAL.write().unwrap_or_else(|e| e.into_inner()).default = format;
+}
+
+/// Sets the format used by `{:#}`
+pub fn set_global_alternate(format: LocationFormat) {
+    GLOBAL.write().unwrap_or_else(|e| e.into_inner()).alternate = format;
+}
+
+/// Writes with the global format, or the global alternate format if `alternate` is set
+pub(crate) fn write_global(
+    f: &mut dyn fmt::Write,
+    alternate: bool,
+    file: &str,
+    original: Option<&str>,
+    line: u32,
+    column: u32,
+) -> fmt::Result {
+    let formats = GLOBAL.read().unwrap_or_else(|e| e.into_inner());
+    let format = if alternate {
+        &formats.alternate
+    } else {
+        &formats.default
+    };
+    format.write_linked(f, file, original, line, column)
+}
+
+#[cfg(all(test, not(locate_error_strip)))]
+mod tests {
+    use super::*;
+    use crate::Location;
+
+    fn render(format: LocationFormat) -> String {
+        let location = Location::from_parts("src/main.rs".to_string(), 40, 19);
+        location.display().format(&format).to_string()
+    }
+
+    #[test]
+    fn test_formats() {
+        assert_eq!(render(LocationFormat::Compact), "src/main.rs:40");
+        assert_eq!(render(LocationFormat::WithColumn), "src/main.rs:40:19");
+        assert_eq!(render(LocationFormat::Rustc), "--> src/main.rs:40:19");
+        let url = render(LocationFormat::EditorUrl);
+        assert!(url.starts_with("vscode://file/"));
+        assert!(url.ends_with("/src/main.rs:40:19"));
+        assert_eq!(
+            render(LocationFormat::Template("{file} line {line}".to_string())),
+            "src/main.rs line 40"
+        );
+        assert_eq!(
+            render(LocationFormat::Template(
+                "{{column}} {unknown} {line".to_string()
+            )),
+            "{19} {unknown} {line"
+        );
+    }
+
+    #[test]
+    fn test_editor_url() {
+        // Links to the original file rather than the remapped one, percent encoded
+        let location = Location::from_parts("/work/my crate/src/main.rs".to_string(), 40, 19);
+        let remap = crate::paths::PathRemap::new().workspace_root("/work/my crate");
+        assert_eq!(
+            location
+                .display()
+                .paths(&remap)
+                .format(&LocationFormat::EditorUrl)
+                .to_string(),
+            "vscode://file/work/my%20crate/src/main.rs:40:19"
+        );
+    }
+
+    #[test]
+    fn test_alternate() {
+        let location = Location::from_parts("src/main.rs".to_string(), 40, 19);
+        assert_eq!(format!("{location}"), "src/main.rs:40:19");
+        assert_eq!(format!("{location:#}"), "--> src/main.rs:40:19");
+    }
+}
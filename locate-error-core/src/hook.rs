@@ -0,0 +1,164 @@
+use crate::Location;
+use core::error::Error;
+use core::fmt::Display;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// A located conversion made by a `From` impl generated by `#[derive(Locate)]`, passed to the
+/// hook set with [`set_hook`]
+#[derive(Clone, Copy)]
+pub struct ConversionEvent<'a> {
+    location: &'a Location,
+    source_type: &'static str,
+    target_type: &'static str,
+    first_hop: bool,
+    error: Option<&'a (dyn Error + 'static)>,
+    message: Option<&'a dyn Display>,
+}
+
+impl<'a> ConversionEvent<'a> {
+    /// Location captured for the conversion
+    pub fn location(&self) -> &'a Location {
+        self.location
+    }
+
+    /// Type name of the converted `#[locate_from]` value
+    pub fn source_type(&self) -> &'static str {
+        self.source_type
+    }
+
+    /// Type name of the error converted into
+    pub fn target_type(&self) -> &'static str {
+        self.target_type
+    }
+
+    /// Whether the converted value did not already carry a location, i.e. this is where the
+    /// error entered the located chain
+    pub fn is_first_hop(&self) -> bool {
+        self.first_hop
+    }
+
+    /// The converted value, if it implements `Error`
+    pub fn error(&self) -> Option<&'a (dyn Error + 'static)> {
+        self.error
+    }
+
+    /// The converted value, if it implements `Display`
+    pub fn message(&self) -> Option<&'a dyn Display> {
+        self.message
+    }
+}
+
+type Hook = Arc<dyn Fn(&ConversionEvent<'_>) + Send + Sync>;
+
+static HOOK: RwLock<Option<Hook>> = RwLock::new(None);
+
+/// Avoids taking the lock on every conversion when no hook is set
+static HOOK_SET: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Conversions made by the hook itself are not reported to it
+    static IN_HOOK: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Sets the hook called on every located conversion, replacing any previous hook. The hook is not
+/// called while locations are turned off at runtime.
+///
+/// The hook runs inline in the `From` impl, so should be cheap. Conversions made while the hook is
+/// running on the same thread are not reported to it.
+pub fn set_hook(hook: impl Fn(&ConversionEvent<'_>) + Send + Sync + 'static) {
+    *HOOK.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(hook));
+    HOOK_SET.store(true, Ordering::Release);
+}
+
+/// Removes the hook set with [`set_hook`]
+pub fn remove_hook() {
+    HOOK_SET.store(false, Ordering::Release);
+    *HOOK.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Reports a conversion to the hook, if set
+pub(crate) fn conversion<S, T>(
+    location: &Location,
+    first_hop: bool,
+    error: Option<&(dyn Error + 'static)>,
+    message: Option<&dyn Display>,
+) {
+    if !HOOK_SET.load(Ordering::Acquire) || IN_HOOK.get() {
+        return;
+    }
+    // Released before calling so the hook may set or remove hooks
+    let Some(hook) = HOOK.read().unwrap_or_else(|e| e.into_inner()).clone() else {
+        return;
+    };
+    let event = ConversionEvent {
+        location,
+        source_type: core::any::type_name::<S>(),
+        target_type: core::any::type_name::<T>(),
+        first_hop,
+        error,
+        message,
+    };
+    IN_HOOK.set(true);
+    // Reset even if the hook panics
+    struct Reset;
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            IN_HOOK.set(false);
+        }
+    }
+    let _reset = Reset;
+    hook(&event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::Inner;
+    use std::sync::Mutex;
+
+    /// Only events converting into this type are recorded, as other tests convert concurrently
+    struct HookTarget;
+
+    #[test]
+    fn test_hook() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        set_hook(move |event| {
+            if event.target_type() != core::any::type_name::<HookTarget>() {
+                return;
+            }
+            // Not reported recursively
+            conversion::<(), HookTarget>(event.location(), false, None, None);
+            recorded.lock().unwrap().push((
+                event.location().clone(),
+                event.source_type(),
+                event.is_first_hop(),
+                event.error().map(ToString::to_string),
+                event.message().map(ToString::to_string),
+            ));
+        });
+
+        let location = Location::new();
+        conversion::<Inner, HookTarget>(&location, true, Some(&Inner), Some(&Inner));
+        conversion::<u8, HookTarget>(&location, false, None, None);
+        remove_hook();
+        conversion::<u8, HookTarget>(&location, false, None, None);
+
+        let events = events.lock().unwrap();
+        assert_eq!(
+            *events,
+            vec![
+                (
+                    location.clone(),
+                    core::any::type_name::<Inner>(),
+                    true,
+                    Some("inner".to_string()),
+                    Some("inner".to_string())
+                ),
+                (location.clone(), "u8", false, None, None),
+            ]
+        );
+    }
+}
@@ -0,0 +1,219 @@
+//! Terminal hyperlinks from a displayed [`Location`](crate::Location) to its source.
+//!
+//! Terminals supporting [OSC 8](https://gist.github.com/egmontkob/eb114294efbcd5adb1944c9f3cb5feda)
+//! make the wrapped text clickable, opening the URL built by a [`UrlScheme`]. Other terminals
+//! show the text unchanged. The URL always uses the original file path made absolute, while the
+//! text uses the remapped path.
+//!
+//! Hyperlinks are off by default. [`set_global`] enables them in reports written by a
+//! [`Renderer`](crate::render::Renderer) while the [`Stream`] the report is printed to, stderr
+//! unless set with [`Renderer::stream`](crate::render::Renderer::stream), is a terminal. A
+//! renderer can override the scheme for a report. Plain `Display` of a `Location` is only linked
+//! with [`DisplayLocation::hyperlink`](crate::DisplayLocation::hyperlink), so messages embedding
+//! a location, [`Snapshot`](crate::Snapshot)s and exported events never contain hyperlinks.
+
+use core::fmt;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+/// The URL a location links to
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UrlScheme {
+    /// `file:///path`, opening the file in the default application
+    File,
+    /// `vscode://file/path:line:column`
+    VsCode,
+    /// `idea://open?file=path&line=line&column=column`, for JetBrains IDEs
+    Idea,
+    /// A template where `{file}`, `{line}` and `{column}` are replaced, e.g.
+    /// `subl://open?url=file://{file}&line={line}`
+    Template(String),
+}
+
+impl UrlScheme {
+    /// Writes the URL of `file`, which is made absolute relative to the current directory
+    pub fn write_url(
+        &self,
+        f: &mut dyn fmt::Write,
+        file: &str,
+        line: u32,
+        column: u32,
+    ) -> fmt::Result {
+        let path = absolute(file);
+        let path = path.to_string_lossy();
+        let path = path.replace('\\', "/");
+        match self {
+            UrlScheme::File => {
+                f.write_str("file://")?;
+                if !path.starts_with('/') {
+                    // Windows drive letters, `file:///C:/...`
+                    f.write_char('/')?;
+                }
+                write_encoded(f, &path)
+            }
+            UrlScheme::VsCode => {
+                f.write_str("vscode://file/")?;
+                write_encoded(f, path.trim_start_matches('/'))?;
+                write!(f, ":{line}:{column}")
+            }
+            UrlScheme::Idea => {
+                f.write_str("idea://open?file=")?;
+                write_encoded(f, &path)?;
+                write!(f, "&line={line}&column={column}")
+            }
+            UrlScheme::Template(template) => {
+                crate::format::write_template(f, template, &path, line, column)
+            }
+        }
+    }
+}
+
+fn absolute(file: &str) -> PathBuf {
+    let path = Path::new(file);
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    std::env::current_dir()
+        .map(|dir| dir.join(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Percent encodes the characters which would end or split the URL
+fn write_encoded(f: &mut dyn fmt::Write, path: &str) -> fmt::Result {
+    for c in path.chars() {
+        match c {
+            ' ' | '%' | '#' | '?' | '&' | '"' | '<' | '>' => write!(f, "%{:02X}", c as u32)?,
+            // Control characters would break out of the escape sequence
+            c if c.is_control() => write!(f, "%{:02X}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    Ok(())
+}
+
+/// The stream a report is printed to, checked by [`Hyperlinks::Auto`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    /// Where `#[locate_error::main]` and the panic hook print reports
+    #[default]
+    Stderr,
+}
+
+impl Stream {
+    pub fn is_terminal(self) -> bool {
+        static STDOUT: OnceLock<bool> = OnceLock::new();
+        static STDERR: OnceLock<bool> = OnceLock::new();
+        match self {
+            Stream::Stdout => *STDOUT.get_or_init(|| std::io::stdout().is_terminal()),
+            Stream::Stderr => *STDERR.get_or_init(|| std::io::stderr().is_terminal()),
+        }
+    }
+}
+
+/// When hyperlinks are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Hyperlinks {
+    /// Only when the stream the report is printed to is a terminal
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl Hyperlinks {
+    pub fn enabled(self, stream: Stream) -> bool {
+        match self {
+            Hyperlinks::Auto => stream.is_terminal(),
+            Hyperlinks::Always => true,
+            Hyperlinks::Never => false,
+        }
+    }
+}
+
+static GLOBAL: RwLock<Option<UrlScheme>> = RwLock::new(None);
+
+/// Sets the scheme used to hyperlink locations in reports written by a
+/// [`Renderer`](crate::render::Renderer) without its own scheme, `None` disables hyperlinks
+pub fn set_global(scheme: Option<UrlScheme>) {
+    *GLOBAL.write().unwrap_or_else(|e| e.into_inner()) = scheme;
+}
+
+/// The global scheme, see [`set_global`]
+pub(crate) fn global() -> Option<UrlScheme> {
+    GLOBAL.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Opens an OSC 8 hyperlink to the location, which must be followed by the text and [`write_end`]
+pub(crate) fn write_start(
+    f: &mut dyn fmt::Write,
+    scheme: &UrlScheme,
+    file: &str,
+    line: u32,
+    column: u32,
+) -> fmt::Result {
+    f.write_str("\x1b]8;;")?;
+    scheme.write_url(f, file, line, column)?;
+    f.write_str("\x1b\\")
+}
+
+pub(crate) fn write_end(f: &mut dyn fmt::Write) -> fmt::Result {
+    f.write_str("\x1b]8;;\x1b\\")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Location;
+
+    fn url(scheme: UrlScheme, file: &str) -> String {
+        let mut url = String::new();
+        scheme.write_url(&mut url, file, 40, 19).unwrap();
+        url
+    }
+
+    #[test]
+    fn test_urls() {
+        assert_eq!(
+            url(UrlScheme::File, "/work/my crate/main.rs"),
+            "file:///work/my%20crate/main.rs"
+        );
+        assert_eq!(
+            url(UrlScheme::VsCode, "/work/main.rs"),
+            "vscode://file/work/main.rs:40:19"
+        );
+        assert_eq!(
+            url(UrlScheme::Idea, "/work/main.rs"),
+            "idea://open?file=/work/main.rs&line=40&column=19"
+        );
+        assert_eq!(
+            url(
+                UrlScheme::Template("editor://{file}@{line}".to_string()),
+                "/work/main.rs"
+            ),
+            "editor:///work/main.rs@40"
+        );
+
+        // Relative paths are resolved against the current directory
+        let relative = url(UrlScheme::File, "src/main.rs");
+        assert!(relative.starts_with("file:///"));
+        assert!(relative.ends_with("/src/main.rs"));
+    }
+
+    #[cfg(not(locate_error_strip))]
+    #[test]
+    fn test_display() {
+        let location = Location::from_parts("/work/main.rs".to_string(), 40, 19);
+        assert_eq!(
+            location
+                .display()
+                .hyperlink(Some(&UrlScheme::VsCode))
+                .to_string(),
+            "\x1b]8;;vscode://file/work/main.rs:40:19\x1b\\/work/main.rs:40:19\x1b]8;;\x1b\\"
+        );
+        assert!(Hyperlinks::Always.enabled(Stream::Stdout));
+        assert!(!Hyperlinks::Never.enabled(Stream::Stderr));
+    }
+}
@@ -1,39 +1,311 @@
+// Tests asserting captured locations are compiled out when locations are stripped, leaving some
+// test helpers unused
+#![cfg_attr(all(test, locate_error_strip), allow(unused_imports, dead_code))]
+
+#[doc(hidden)]
+pub mod __private;
+mod chain;
+#[cfg(feature = "eyre")]
+pub mod eyre;
+pub mod fingerprint;
+#[cfg(test)]
+mod fixtures;
+pub mod format;
+mod hook;
+pub mod hyperlink;
+mod located;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "miette")]
+pub mod miette;
+pub mod otel;
+pub mod panic;
+mod parse;
+pub mod paths;
+pub mod permalink;
+pub mod render;
+mod report;
+pub mod runtime;
+#[cfg(feature = "sentry")]
+pub mod sentry;
+mod snapshot;
+pub mod snippet;
+pub mod style;
+#[cfg(feature = "tracing")]
+pub mod tracing;
+
+pub use chain::{Chain, Hop};
+pub use hook::{ConversionEvent, remove_hook, set_hook};
+pub use located::{Located, as_located, register};
+pub use parse::ParseLocationError;
+pub use report::Report;
+pub use snapshot::{Snapshot, SnapshotHop};
+
 /// Represents the location in a file, used for error reporting
-#[derive(Debug)]
+///
+/// Comparison, ordering and hashing only consider the file, line and column (in that order), not
+/// any captured traces.
+///
+/// With the `serde` feature only the file, line and column are serialized. Captured traces are
+/// kept as text by [`Snapshot`].
+///
+/// Building with `RUSTFLAGS="--cfg locate_error_strip"` strips locations: `Location` becomes a
+/// zero sized type without the public fields, nothing is captured by `location!` or the generated
+/// `From` impls, no file paths are compiled into the binary, and `Display` writes `<stripped>`.
+/// Code using the accessor methods instead of the fields compiles either way.
+///
+/// `Location` is `#[non_exhaustive]` whichever features are enabled, since the `spantrace` and
+/// `backtrace` features add private fields. Build one with [`Location::from_parts`], the only
+/// constructor taking a file, line and column.
+#[cfg(not(locate_error_strip))]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub struct Location {
     pub file: String,
     pub line: u32,
     pub column: u32,
+    #[cfg(feature = "spantrace")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    span_trace: Option<tracing_error::SpanTrace>,
+    #[cfg(feature = "backtrace")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    backtrace: Option<std::sync::Arc<std::backtrace::Backtrace>>,
 }
 
+/// Represents the location in a file, stripped by `--cfg locate_error_strip`
+#[cfg(locate_error_strip)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Location {}
+
+/// Written by `Display` when locations are stripped
+const STRIPPED: &str = "<stripped>";
+
 #[allow(clippy::new_without_default)]
 impl Location {
-    #[track_caller]
+    #[cfg_attr(not(locate_error_strip), track_caller)]
     pub fn new() -> Self {
         location!()
     }
+
+    /// Builds a location, `const` unless locations are stripped
+    ///
+    /// This is the only way to build a `Location` from its parts outside this crate, as struct
+    /// literals are rejected by `#[non_exhaustive]`.
+    #[cfg(not(locate_error_strip))]
+    pub const fn from_parts(file: String, line: u32, column: u32) -> Self {
+        Location {
+            file,
+            line,
+            column,
+            #[cfg(feature = "spantrace")]
+            span_trace: None,
+            #[cfg(feature = "backtrace")]
+            backtrace: None,
+        }
+    }
+
+    /// Builds a location, `const` unless locations are stripped
+    #[cfg(locate_error_strip)]
+    pub fn from_parts(_file: String, _line: u32, _column: u32) -> Self {
+        Location {}
+    }
+
+    #[cfg(not(locate_error_strip))]
+    pub(crate) fn from_caller(caller: __private::Caller) -> Self {
+        Location::from(caller)
+    }
+
+    #[cfg(locate_error_strip)]
+    pub(crate) fn from_caller(_: __private::Caller) -> Self {
+        Location {}
+    }
+
+    /// The file, empty when locations are stripped
+    pub fn file(&self) -> &str {
+        #[cfg(not(locate_error_strip))]
+        return &self.file;
+        #[cfg(locate_error_strip)]
+        return "";
+    }
+
+    /// The line, `0` when locations are stripped
+    pub fn line(&self) -> u32 {
+        #[cfg(not(locate_error_strip))]
+        return self.line;
+        #[cfg(locate_error_strip)]
+        return 0;
+    }
+
+    /// The column, `0` when locations are stripped
+    pub fn column(&self) -> u32 {
+        #[cfg(not(locate_error_strip))]
+        return self.column;
+        #[cfg(locate_error_strip)]
+        return 0;
+    }
+
+    fn key(&self) -> (&str, u32, u32) {
+        (self.file(), self.line(), self.column())
+    }
+
+    /// The span trace captured by the first located conversion of a chain
+    #[cfg(feature = "spantrace")]
+    pub fn span_trace(&self) -> Option<&tracing_error::SpanTrace> {
+        #[cfg(not(locate_error_strip))]
+        return self.span_trace.as_ref();
+        #[cfg(locate_error_strip)]
+        return None;
+    }
+
+    /// The backtrace captured by the first located conversion of a chain.
+    ///
+    /// Captured with `Backtrace::capture`, so is only resolved when enabled by the
+    /// `RUST_LIB_BACKTRACE` or `RUST_BACKTRACE` environment variables.
+    #[cfg(feature = "backtrace")]
+    pub fn backtrace(&self) -> Option<&std::backtrace::Backtrace> {
+        #[cfg(not(locate_error_strip))]
+        return self.backtrace.as_deref();
+        #[cfg(locate_error_strip)]
+        return None;
+    }
+}
+
+/// Discards the location when locations are stripped
+impl From<&core::panic::Location<'_>> for Location {
+    fn from(location: &core::panic::Location<'_>) -> Self {
+        Location::from_parts(
+            location.file().to_string(),
+            location.line(),
+            location.column(),
+        )
+    }
+}
+
+impl PartialEq for Location {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Location {}
+
+impl core::hash::Hash for Location {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl PartialOrd for Location {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
+impl Ord for Location {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Writes the location in the global [`format::LocationFormat`], or the global alternate format
+/// for `{:#}`, with the file remapped by the global [`paths::PathRemap`]
 impl core::fmt::Display for Location {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+        core::fmt::Display::fmt(&self.display(), f)
+    }
+}
+
+impl Location {
+    /// Displays the location, allowing the global remap and format to be overridden
+    pub fn display(&self) -> DisplayLocation<'_> {
+        DisplayLocation {
+            location: self,
+            remap: None,
+            format: None,
+            hyperlink: None,
+        }
+    }
+
+    /// Displays the location with `remap` in place of the global remap
+    pub fn display_with<'a>(&'a self, remap: &'a paths::PathRemap) -> DisplayLocation<'a> {
+        self.display().paths(remap)
+    }
+}
+
+/// Displays a [`Location`], see [`Location::display`]
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayLocation<'a> {
+    location: &'a Location,
+    remap: Option<&'a paths::PathRemap>,
+    format: Option<&'a format::LocationFormat>,
+    hyperlink: Option<&'a hyperlink::UrlScheme>,
+}
+
+impl<'a> DisplayLocation<'a> {
+    /// Remaps the file with `remap` in place of the global remap
+    pub fn paths(mut self, remap: &'a paths::PathRemap) -> Self {
+        self.remap = Some(remap);
+        self
+    }
+
+    /// Writes the location in `format`, regardless of the global formats and `{:#}`
+    pub fn format(mut self, format: &'a format::LocationFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Wraps the location in a terminal hyperlink to the source file with `scheme`, regardless of
+    /// where it is written. `None`, the default, writes no hyperlink, see [`hyperlink`]
+    pub fn hyperlink(mut self, scheme: Option<&'a hyperlink::UrlScheme>) -> Self {
+        self.hyperlink = scheme;
+        self
+    }
+}
+
+impl core::fmt::Display for DisplayLocation<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if cfg!(locate_error_strip) {
+            return f.write_str(STRIPPED);
+        }
+        let (file, line, column) = self.location.key();
+        // Redacted locations are hashed without remapping and never linked to their source
+        let (remapped, original, hyperlink) = match runtime::mode() {
+            runtime::Mode::On => match self.remap {
+                Some(remap) => (remap.apply(file), Some(file), self.hyperlink),
+                None => (paths::remap_global(file), Some(file), self.hyperlink),
+            },
+            runtime::Mode::Redact => (runtime::hash_path(file).into(), None, None),
+            runtime::Mode::Off => return Ok(()),
+        };
+        if let Some(scheme) = hyperlink {
+            hyperlink::write_start(f, scheme, file, line, column)?;
+        }
+        match self.format {
+            Some(format) => format.write_linked(f, &remapped, original, line, column)?,
+            None => {
+                let alternate = f.alternate();
+                format::write_global(f, alternate, &remapped, original, line, column)?
+            }
+        }
+        if hyperlink.is_some() {
+            hyperlink::write_end(f)?;
+        }
+        Ok(())
     }
 }
 
 /// Utility to get the location of the caller
 #[macro_export]
 macro_rules! location {
-    () => {{
-        let caller = ::core::panic::Location::caller();
-        $crate::Location {
-            file: caller.file().to_string(),
-            line: caller.line(),
-            column: caller.column(),
-        }
-    }};
+    () => {
+        $crate::__private::location($crate::__private::caller!())
+    };
 }
 
-#[cfg(test)]
+#[cfg(all(test, not(locate_error_strip)))]
 mod tests {
     use super::*;
 
@@ -44,31 +316,49 @@ mod tests {
         let column = 24;
         let line = line!();
         let location = location!();
-        assert!(matches!(
-            location,
-            Location {
-                file: _,
-                line: _,
-                column: _
-            }
-        ));
-        assert_eq!(location.file, file);
-        assert_eq!(location.line, line + 1);
-        assert_eq!(location.column, column);
+        assert_eq!(location.file(), file);
+        assert_eq!(location.line(), line + 1);
+        assert_eq!(location.column(), column);
 
         let column = 24;
         let line = line!();
         let location = Location::new();
-        assert!(matches!(
-            location,
-            Location {
-                file: _,
-                line: _,
-                column: _
-            }
-        ));
-        assert_eq!(location.file, file);
-        assert_eq!(location.line, line + 1);
-        assert_eq!(location.column, column);
+        assert_eq!(location.file(), file);
+        assert_eq!(location.line(), line + 1);
+        assert_eq!(location.column(), column);
+    }
+
+    #[test]
+    // The captured backtrace is lazily resolved, but is not used for hashing or ordering
+    #[allow(clippy::mutable_key_type)]
+    fn test_value_traits() {
+        use std::collections::{BTreeSet, HashSet};
+
+        const LOCATION: Location = Location::from_parts(String::new(), 1, 2);
+        assert_eq!(LOCATION.file(), "");
+        assert_eq!(LOCATION.line(), 1);
+        assert_eq!(LOCATION.column(), 2);
+
+        let a = Location::from_parts("a.rs".to_string(), 2, 1);
+        let b = Location::from_parts("a.rs".to_string(), 10, 1);
+        let c = Location::from_parts("b.rs".to_string(), 1, 1);
+        assert_eq!(a.clone(), a);
+        assert_ne!(a, b);
+        // Ordered by file, then line, then column
+        assert!(a < b && b < c);
+        assert!(a < Location::from_parts("a.rs".to_string(), 2, 3));
+
+        let set: HashSet<_> = [a.clone(), a.clone(), b.clone()].into_iter().collect();
+        assert_eq!(set.len(), 2);
+        let set: BTreeSet<_> = [c.clone(), b.clone(), a.clone()].into_iter().collect();
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![a, b, c]);
+
+        let line = line!();
+        let caller = core::panic::Location::caller();
+        let location = Location::from(caller);
+        assert_eq!(location.file(), file!());
+        assert_eq!(location.line(), line + 1);
+        assert_eq!(location.column(), 22);
+        assert_eq!(location, Location::from(caller));
     }
 }
@@ -0,0 +1,68 @@
+use crate::Location;
+use core::any::TypeId;
+use core::error::Error;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Implemented by `#[derive(Locate)]` types to expose the location captured on conversion and
+/// the `#[locate_from]` value it was converted from.
+///
+/// A `dyn Error` can only be recognised as `Located` once its type is [`register`]ed, which the
+/// generated `From` impls do for non-generic types and for the `#[locate_from]` types they convert
+/// from. Generic types, and types implementing `Located` by hand, are otherwise treated as plain
+/// errors by [`Chain`](crate::Chain), reports and renderers until `register` is called for each
+/// instantiation, e.g. `register::<MyError<io::Error>>()`. A `#[locate_from]` field whose type
+/// is a generic parameter is only followed by `Chain` when it is also marked as the `#[source]`.
+pub trait Located {
+    /// The location captured by the `From` impl, or `None` for variants without `#[locate_from]`
+    fn location(&self) -> Option<&Location>;
+
+    /// The `#[locate_from]` value, if it implements `Error`
+    fn located_source(&self) -> Option<&(dyn Error + 'static)>;
+
+    /// Name of the implementing type, used when rendering chains
+    fn type_name(&self) -> &'static str {
+        core::any::type_name::<Self>()
+    }
+}
+
+type Downcast = for<'a> fn(&'a (dyn Error + 'static)) -> Option<&'a dyn Located>;
+
+/// Types which can be recovered as `dyn Located` from a `dyn Error`
+static REGISTRY: RwLock<Option<HashMap<TypeId, Downcast>>> = RwLock::new(None);
+
+/// Registers `T` so it can be recognised as `Located` when walking a chain of `dyn Error`s.
+///
+/// The `From` impls generated by `#[derive(Locate)]` call this for non-generic target types and
+/// for their `#[locate_from]` types, so this only needs to be called for generic types and for
+/// located errors which are constructed by hand.
+pub fn register<T: Located + Error + 'static>() {
+    let type_id = TypeId::of::<T>();
+    let registered = REGISTRY
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .is_some_and(|registry| registry.contains_key(&type_id));
+    if registered {
+        return;
+    }
+    REGISTRY
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_insert_with(HashMap::new)
+        .entry(type_id)
+        .or_insert(|error| error.downcast_ref::<T>().map(|t| t as &dyn Located));
+}
+
+/// Recovers the `Located` impl of a registered error type.
+///
+/// `dyn Error` does not expose its `TypeId` on stable Rust, so each registered type is tried in
+/// turn.
+pub fn as_located<'a>(error: &'a (dyn Error + 'static)) -> Option<&'a dyn Located> {
+    REGISTRY
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()?
+        .values()
+        .find_map(|downcast| downcast(error))
+}
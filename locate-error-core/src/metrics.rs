@@ -0,0 +1,181 @@
+//! In-process counters of located conversions, keyed by the location and the target type.
+//!
+//! Every conversion made by a generated `From` impl is counted, with no instrumentation needed
+//! in the converting crates. [`snapshot`] copies the counters, which can be exported in the
+//! Prometheus text exposition format with [`Counts::to_prometheus`].
+
+use crate::{__private::Caller, Location, paths};
+use core::fmt::Write;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// File, line and column of a conversion, borrowed from `core::panic::Location` so counting does
+/// not allocate
+type Site = (&'static str, u32, u32);
+
+static COUNTERS: RwLock<Option<HashMap<(Site, &'static str), AtomicU64>>> = RwLock::new(None);
+
+/// Counts a conversion into `target_type` at the caller. Conversions are counted under an empty
+/// location when locations are stripped, and not counted when they are turned off at runtime.
+#[allow(unused_variables)]
+pub(crate) fn conversion(caller: Caller, target_type: &'static str) {
+    #[cfg(not(locate_error_strip))]
+    let site = (caller.file(), caller.line(), caller.column());
+    #[cfg(locate_error_strip)]
+    let site = ("", 0, 0);
+    record(site, target_type);
+}
+
+fn record(site: Site, target_type: &'static str) {
+    let key = (site, target_type);
+    if let Some(counter) = COUNTERS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .and_then(|counters| counters.get(&key))
+    {
+        counter.fetch_add(1, Ordering::Relaxed);
+        return;
+    }
+    COUNTERS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_insert_with(HashMap::new)
+        .entry(key)
+        .or_default()
+        .fetch_add(1, Ordering::Relaxed);
+}
+
+/// The number of conversions at a location into a type
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SiteCount {
+    pub location: Location,
+    pub target_type: &'static str,
+    pub count: u64,
+}
+
+/// Copy of the counters, see [`snapshot`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Counts {
+    /// Ordered by location, then target type
+    pub sites: Vec<SiteCount>,
+}
+
+/// Copies the current counters
+pub fn snapshot() -> Counts {
+    let counters = COUNTERS.read().unwrap_or_else(|e| e.into_inner());
+    let mut sites: Vec<_> = counters
+        .iter()
+        .flatten()
+        .map(|(&((file, line, column), target_type), count)| SiteCount {
+            location: Location::from_parts(file.to_string(), line, column),
+            target_type,
+            count: count.load(Ordering::Relaxed),
+        })
+        .collect();
+    sites.sort();
+    Counts { sites }
+}
+
+/// Sets all counters back to zero
+pub fn reset() {
+    *COUNTERS.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+impl Counts {
+    /// Renders the counts in the Prometheus text exposition format as the counter
+    /// `locate_error_conversions_total`, labelled with `file`, `line`, `column` and
+    /// `target_type`. Files are remapped by the global remap, or hashed when locations are
+    /// redacted at runtime.
+    pub fn to_prometheus(&self) -> String {
+        const NAME: &str = "locate_error_conversions_total";
+        let mut text = format!(
+            "# HELP {NAME} Located error conversions by source location and target type.\n\
+             # TYPE {NAME} counter\n"
+        );
+        for site in &self.sites {
+            let file = paths::export(site.location.file());
+            // Writing to a `String` can't fail
+            let _ = writeln!(
+                text,
+                "{NAME}{{file=\"{}\",line=\"{}\",column=\"{}\",target_type=\"{}\"}} {}",
+                escape(&file),
+                site.location.line(),
+                site.location.column(),
+                escape(site.target_type),
+                site.count
+            );
+        }
+        text
+    }
+}
+
+/// Escapes a label value
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(all(test, not(locate_error_strip)))]
+mod tests {
+    use super::*;
+
+    /// Only counts into this type are checked, as other tests convert concurrently
+    struct MetricsTarget;
+
+    #[test]
+    fn test_record() {
+        let target = core::any::type_name::<MetricsTarget>();
+        let site = ("src/main.rs", 40, 19);
+        record(site, target);
+        record(site, target);
+        record(("src/lib.rs", 1, 1), target);
+
+        let counts: Vec<_> = snapshot()
+            .sites
+            .into_iter()
+            .filter(|site| site.target_type == target)
+            .map(|site| (site.location.to_string(), site.count))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                ("src/lib.rs:1:1".to_string(), 1),
+                ("src/main.rs:40:19".to_string(), 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prometheus() {
+        let counts = Counts {
+            sites: vec![
+                SiteCount {
+                    location: Location::from_parts("src/main.rs".to_string(), 40, 19),
+                    target_type: "app::Error",
+                    count: 3,
+                },
+                SiteCount {
+                    location: Location::from_parts(r#"C:\src\"a".rs"#.to_string(), 1, 2),
+                    target_type: "app::Other<'_>",
+                    count: 1,
+                },
+            ],
+        };
+        assert_eq!(
+            counts.to_prometheus(),
+            "# HELP locate_error_conversions_total Located error conversions by source location and target type.\n\
+             # TYPE locate_error_conversions_total counter\n\
+             locate_error_conversions_total{file=\"src/main.rs\",line=\"40\",column=\"19\",target_type=\"app::Error\"} 3\n\
+             locate_error_conversions_total{file=\"C:\\\\src\\\\\\\"a\\\".rs\",line=\"1\",column=\"2\",target_type=\"app::Other<'_>\"} 1\n"
+        );
+        assert_eq!(
+            Counts::default().to_prometheus().lines().count(),
+            2,
+            "only the metadata is written without counts"
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! A [`miette::Diagnostic`] built from the locations of an error chain
+
+use crate::runtime::{self, Mode};
+use crate::{Chain, Hop, Location};
+use core::error::Error;
+use core::fmt;
+use miette::{Diagnostic, LabeledSpan, NamedSource, SourceCode, SourceOffset, SourceSpan};
+
+/// Snapshot of an error chain which renders each error's location with `miette`.
+///
+/// The location of the outermost error is given as the help text, and each of its causes is
+/// exposed as a related diagnostic with its own location. Source snippets can be loaded with
+/// [`LocatedDiagnostic::with_snippets`].
+#[derive(Debug)]
+pub struct LocatedDiagnostic {
+    message: String,
+    location: Option<Location>,
+    snippet: Option<(NamedSource<String>, SourceSpan)>,
+    related: Vec<LocatedDiagnostic>,
+}
+
+impl LocatedDiagnostic {
+    pub fn new(error: &(dyn Error + 'static)) -> Self {
+        let mut hops = Chain::new(error);
+        // A chain always contains at least the error itself
+        let mut diagnostic = Self::from_hop(hops.next().unwrap());
+        diagnostic.related = hops.map(Self::from_hop).collect();
+        diagnostic
+    }
+
+    fn from_hop(hop: Hop<'_>) -> Self {
+        LocatedDiagnostic {
+            message: hop.error().to_string(),
+            location: hop
+                .location()
+                .filter(|_| runtime::mode() != Mode::Off)
+                .cloned(),
+            snippet: None,
+            related: vec![],
+        }
+    }
+
+    /// Reads the source file of each location, labelling the line and column.
+    ///
+    /// Locations whose files are not present on disk (e.g. released binaries) or which are
+    /// redacted are left without a snippet.
+    pub fn with_snippets(mut self) -> Self {
+        self.load_snippet();
+        for related in &mut self.related {
+            related.load_snippet();
+        }
+        self
+    }
+
+    fn load_snippet(&mut self) {
+        let Some(location) = &self.location else {
+            return;
+        };
+        if runtime::mode() == Mode::Redact {
+            return;
+        }
+        let Ok(source) = std::fs::read_to_string(location.file()) else {
+            return;
+        };
+        let offset = SourceOffset::from_location(
+            &source,
+            location.line() as usize,
+            location.column() as usize,
+        );
+        let len = source[offset.offset()..]
+            .chars()
+            .next()
+            .map_or(0, char::len_utf8);
+        self.snippet = Some((
+            NamedSource::new(location.file(), source),
+            SourceSpan::new(offset, len),
+        ));
+    }
+}
+
+impl fmt::Display for LocatedDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for LocatedDiagnostic {}
+
+impl Diagnostic for LocatedDiagnostic {
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        let location = self.location.as_ref()?;
+        Some(Box::new(format!("at {location}")))
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        let (source, _) = self.snippet.as_ref()?;
+        Some(source)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let (_, span) = self.snippet.as_ref()?;
+        let label = LabeledSpan::new_with_span(Some("occurred here".to_string()), *span);
+        Some(Box::new(core::iter::once(label)))
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        if self.related.is_empty() {
+            return None;
+        }
+        Some(Box::new(
+            self.related
+                .iter()
+                .map(|related| related as &dyn Diagnostic),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::Outer;
+    use miette::NarratableReportHandler;
+
+    #[test]
+    fn test_diagnostic() {
+        let outer = Outer::new();
+        let diagnostic = LocatedDiagnostic::new(&outer);
+        assert_eq!(diagnostic.to_string(), "outer");
+        assert_eq!(
+            diagnostic.help().unwrap().to_string(),
+            format!("at {}", outer.location)
+        );
+        let related: Vec<_> = diagnostic.related().unwrap().collect();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].to_string(), "inner");
+        assert!(related[0].help().is_none());
+    }
+
+    #[cfg(not(locate_error_strip))]
+    #[test]
+    fn test_snippets() {
+        // Points at `column!()` in this file
+        let (line, column) = (line!(), column!());
+        let location = Location::from_parts(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/src/miette.rs").to_string(),
+            line,
+            column,
+        );
+        let diagnostic = LocatedDiagnostic::new(&Outer::at(location)).with_snippets();
+        let mut rendered = String::new();
+        NarratableReportHandler::new()
+            .render_report(&mut rendered, &diagnostic)
+            .unwrap();
+        assert!(rendered.contains("let (line, column) = (line!(), column!());"));
+        assert!(rendered.contains("occurred here"));
+
+        // Missing files are skipped
+        let location = Location::from_parts("does/not/exist.rs".to_string(), 1, 1);
+        let diagnostic = LocatedDiagnostic::new(&Outer::at(location)).with_snippets();
+        assert!(diagnostic.source_code().is_none());
+        assert!(diagnostic.labels().is_none());
+    }
+}
@@ -0,0 +1,133 @@
+//! OpenTelemetry semantic convention attributes for located errors.
+//!
+//! The attributes are plain key/value pairs, so they can be attached to a span or event by any
+//! OpenTelemetry SDK without this crate depending on one:
+//! - `exception.type`: type name of the error, when it is a registered `Located` type
+//! - `exception.message`: `Display` of the error
+//! - `exception.stacktrace`: the error chain with the location of each error, as rendered by a
+//!   plain [`Renderer`]
+//! - `code.filepath`, `code.lineno`, `code.column`: the location of the outermost located error
+//!   in the chain, with the file remapped by the global [`PathRemap`](crate::paths::PathRemap) or
+//!   hashed when redacted, left out when locations are turned off at runtime
+
+use crate::hyperlink::Hyperlinks;
+use crate::render::Renderer;
+use crate::runtime::{self, Mode};
+use crate::{Chain, Location, paths};
+use core::error::Error;
+use core::fmt;
+
+/// Value of an [`Attribute`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    String(String),
+    Int(i64),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::String(value) => write!(f, "{value}"),
+            Value::Int(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// A key/value pair following the OpenTelemetry semantic conventions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attribute {
+    pub key: &'static str,
+    pub value: Value,
+}
+
+impl Attribute {
+    fn new(key: &'static str, value: Value) -> Self {
+        Attribute { key, value }
+    }
+}
+
+/// The exception and code attributes of an error and its sources, without hyperlinks
+pub fn exception_attributes(error: &(dyn Error + 'static)) -> Vec<Attribute> {
+    let mut attributes = Vec::new();
+    if let Some(type_name) = Chain::new(error).next().and_then(|hop| hop.type_name()) {
+        attributes.push(Attribute::new(
+            "exception.type",
+            Value::String(type_name.to_string()),
+        ));
+    }
+    attributes.push(Attribute::new(
+        "exception.message",
+        Value::String(error.to_string()),
+    ));
+    let renderer = Renderer::new().hyperlinks(Hyperlinks::Never);
+    attributes.push(Attribute::new(
+        "exception.stacktrace",
+        Value::String(renderer.render(error).to_string()),
+    ));
+    if let Some(location) = Chain::new(error).find_map(|hop| hop.location())
+        && runtime::mode() != Mode::Off
+    {
+        attributes.extend(code_attributes(location));
+    }
+    attributes
+}
+
+fn code_attributes(location: &Location) -> [Attribute; 3] {
+    [
+        Attribute::new(
+            "code.filepath",
+            Value::String(paths::export(location.file())),
+        ),
+        Attribute::new("code.lineno", Value::Int(location.line().into())),
+        Attribute::new("code.column", Value::Int(location.column().into())),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{Inner, Outer};
+
+    #[cfg(not(locate_error_strip))]
+    #[test]
+    fn test_exception_attributes() {
+        let outer = Outer::at(Location::from_parts("src/main.rs".to_string(), 40, 19));
+        let attributes = exception_attributes(&outer);
+        let attributes: Vec<_> = attributes
+            .iter()
+            .map(|attribute| (attribute.key, attribute.value.to_string()))
+            .collect();
+        assert_eq!(
+            attributes,
+            vec![
+                (
+                    "exception.type",
+                    core::any::type_name::<Outer>().to_string()
+                ),
+                ("exception.message", "outer".to_string()),
+                (
+                    "exception.stacktrace",
+                    "outer\n    at src/main.rs:40:19\n\nCaused by:\n   0: inner".to_string()
+                ),
+                ("code.filepath", "src/main.rs".to_string()),
+                ("code.lineno", "40".to_string()),
+                ("code.column", "19".to_string()),
+            ]
+        );
+        assert!(matches!(
+            exception_attributes(&outer)[4].value,
+            Value::Int(40)
+        ));
+    }
+
+    #[test]
+    fn test_unlocated() {
+        assert_eq!(
+            exception_attributes(&Inner),
+            vec![
+                Attribute::new("exception.message", Value::String("inner".to_string())),
+                Attribute::new("exception.stacktrace", Value::String("inner".to_string())),
+            ]
+        );
+    }
+}
@@ -0,0 +1,250 @@
+//! Reporting of panics in the style of rendered error reports, and conversion of panics into
+//! located errors.
+
+use crate::runtime::{self, Mode};
+use crate::{Located, Location, register};
+use core::any::Any;
+use core::cell::{Cell, RefCell};
+use core::error::Error;
+use core::fmt;
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::panic::{PanicHookInfo, UnwindSafe};
+use std::sync::{Arc, Mutex, Once};
+
+/// Sets a panic hook printing the message and location of each panic to stderr, followed by a
+/// backtrace when enabled by `RUST_BACKTRACE`:
+///
+/// ```text
+/// thread 'main' panicked: index out of bounds: the len is 3 but the index is 7
+///     at src/main.rs:12:5
+/// ```
+///
+/// While [`catch_unwind_located`] runs, its own hook is set in front of this one and passes on
+/// the panics it does not catch.
+pub fn install_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        let thread = std::thread::current();
+        let report = PanicReport {
+            thread: thread.name(),
+            message: payload_message(info.payload()),
+            location: info.location().map(Location::from),
+        };
+        let backtrace = Backtrace::capture();
+        match backtrace.status() {
+            BacktraceStatus::Captured => eprintln!("{report}\n\nStack backtrace:\n{backtrace}"),
+            _ => eprintln!("{report}"),
+        }
+    }));
+}
+
+/// A panic caught by [`catch_unwind_located`]
+#[derive(Debug, Clone)]
+pub struct PanicError {
+    message: String,
+    location: Option<Location>,
+}
+
+impl PanicError {
+    fn new(message: String, location: Option<Location>) -> Self {
+        static REGISTER: Once = Once::new();
+        REGISTER.call_once(register::<PanicError>);
+        PanicError { message, location }
+    }
+
+    /// The message passed to `panic!`, or `Box<dyn Any>` for payloads other than strings
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for PanicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "panicked: {}", self.message)
+    }
+}
+
+impl Error for PanicError {}
+
+impl Located for PanicError {
+    /// Location of the panic, `None` if it was not captured
+    fn location(&self) -> Option<&Location> {
+        self.location.as_ref()
+    }
+
+    fn located_source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+thread_local! {
+    /// Number of nested `catch_unwind_located` calls running on this thread
+    static CATCHING: Cell<usize> = const { Cell::new(0) };
+    /// Location of the last panic caught on this thread
+    static CAUGHT: RefCell<Option<Location>> = const { RefCell::new(None) };
+}
+
+type Hook = Box<dyn Fn(&PanicHookInfo<'_>) + Sync + Send + 'static>;
+
+/// Number of `catch_unwind_located` calls running in the process, and the hook replaced while
+/// any are running
+static SCOPE: Mutex<(usize, Option<Arc<Hook>>)> = Mutex::new((0, None));
+
+/// Runs `f`, converting a panic into a [`PanicError`] carrying the panic's message and location.
+///
+/// While any call is running, the location is captured by a panic hook set in front of the
+/// current hook, such as the one set by [`install_hook`]. Panics caught on threads running
+/// `catch_unwind_located` are returned rather than printed, and other panics are passed on to the
+/// previous hook. The previous hook is restored when the last running call returns, replacing any
+/// hook set with `std::panic::set_hook` in the meantime.
+pub fn catch_unwind_located<F, R>(f: F) -> Result<R, PanicError>
+where
+    F: FnOnce() -> R + UnwindSafe,
+{
+    enter_scope();
+    CAUGHT.take();
+    CATCHING.set(CATCHING.get() + 1);
+    let result = std::panic::catch_unwind(f);
+    CATCHING.set(CATCHING.get() - 1);
+    exit_scope();
+    result.map_err(|payload| PanicError::new(payload_message(&*payload).to_string(), CAUGHT.take()))
+}
+
+/// Sets the capturing hook in front of the current hook, unless another call already did
+fn enter_scope() {
+    let mut scope = SCOPE.lock().unwrap_or_else(|e| e.into_inner());
+    if scope.0 == 0 {
+        let previous = Arc::new(std::panic::take_hook());
+        let chained = previous.clone();
+        std::panic::set_hook(Box::new(move |info| {
+            if !capture(info) {
+                chained(info);
+            }
+        }));
+        scope.1 = Some(previous);
+    }
+    scope.0 += 1;
+}
+
+/// Restores the previous hook once no calls are running
+fn exit_scope() {
+    let mut scope = SCOPE.lock().unwrap_or_else(|e| e.into_inner());
+    scope.0 -= 1;
+    if scope.0 > 0 {
+        return;
+    }
+    // Dropping the capturing hook releases its reference to the previous hook
+    drop(std::panic::take_hook());
+    if let Some(previous) = scope.1.take() {
+        match Arc::try_unwrap(previous) {
+            Ok(previous) => std::panic::set_hook(previous),
+            Err(previous) => std::panic::set_hook(Box::new(move |info| previous(info))),
+        }
+    }
+}
+
+/// Records the location of a panic within `catch_unwind_located`, returning whether it was
+/// recorded
+fn capture(info: &PanicHookInfo<'_>) -> bool {
+    if CATCHING.get() == 0 {
+        return false;
+    }
+    let location = info
+        .location()
+        .filter(|_| runtime::mode() != Mode::Off)
+        .map(Location::from);
+    CAUGHT.set(location);
+    true
+}
+
+/// The message of a panic payload, as passed to `panic!`
+fn payload_message(payload: &(dyn Any + Send)) -> &str {
+    match payload.downcast_ref::<&'static str>() {
+        Some(message) => message,
+        None => match payload.downcast_ref::<String>() {
+            Some(message) => message,
+            None => "Box<dyn Any>",
+        },
+    }
+}
+
+struct PanicReport<'a> {
+    thread: Option<&'a str>,
+    message: &'a str,
+    location: Option<Location>,
+}
+
+impl fmt::Display for PanicReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "thread '{}' panicked: {}",
+            self.thread.unwrap_or("<unnamed>"),
+            self.message
+        )?;
+        if let Some(location) = &self.location
+            && runtime::mode() != Mode::Off
+        {
+            write!(f, "\n    at {location}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_message() {
+        let payload: Box<dyn Any + Send> = Box::new("static");
+        assert_eq!(payload_message(&*payload), "static");
+        let payload: Box<dyn Any + Send> = Box::new(format!("formatted {}", 1));
+        assert_eq!(payload_message(&*payload), "formatted 1");
+        let payload: Box<dyn Any + Send> = Box::new(1);
+        assert_eq!(payload_message(&*payload), "Box<dyn Any>");
+    }
+
+    #[cfg(not(locate_error_strip))]
+    #[test]
+    fn test_catch_unwind_located() {
+        assert_eq!(catch_unwind_located(|| 1).unwrap(), 1);
+
+        let line = line!() + 1;
+        let error = catch_unwind_located(|| panic!("failed job {}", 7)).unwrap_err();
+        assert_eq!(error.message(), "failed job 7");
+        assert_eq!(error.to_string(), "panicked: failed job 7");
+        let location = crate::as_located(&error).unwrap().location().unwrap();
+        assert_eq!(location.file(), file!());
+        assert_eq!(location.line(), line);
+
+        // Nested calls catch their own panics
+        let outer = catch_unwind_located(|| {
+            let inner = catch_unwind_located(|| panic!("inner")).unwrap_err();
+            assert_eq!(inner.message(), "inner");
+            std::panic::panic_any(404)
+        })
+        .unwrap_err();
+        assert_eq!(outer.message(), "Box<dyn Any>");
+        assert!(outer.location().is_some());
+    }
+
+    #[cfg(not(locate_error_strip))]
+    #[test]
+    fn test_report() {
+        let report = PanicReport {
+            thread: Some("main"),
+            message: "oops",
+            location: Some(Location::from_parts("src/main.rs".to_string(), 12, 5)),
+        };
+        assert_eq!(
+            report.to_string(),
+            "thread 'main' panicked: oops\n    at src/main.rs:12:5"
+        );
+        let report = PanicReport {
+            thread: None,
+            message: "oops",
+            location: None,
+        };
+        assert_eq!(report.to_string(), "thread '<unnamed>' panicked: oops");
+    }
+}
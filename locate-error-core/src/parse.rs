@@ -0,0 +1,125 @@
+use crate::Location;
+use core::fmt;
+use core::num::ParseIntError;
+use core::str::FromStr;
+
+/// Error parsing a [`Location`] from `file:line:column`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseLocationError {
+    /// Fewer than two `:` separators were found
+    MissingSeparator,
+    /// Nothing precedes the line number
+    EmptyFile,
+    InvalidLine(ParseIntError),
+    InvalidColumn(ParseIntError),
+}
+
+impl fmt::Display for ParseLocationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseLocationError::MissingSeparator => {
+                write!(f, "expected a location of the form `file:line:column`")
+            }
+            ParseLocationError::EmptyFile => write!(f, "location has an empty file path"),
+            ParseLocationError::InvalidLine(e) => write!(f, "invalid line number: {e}"),
+            ParseLocationError::InvalidColumn(e) => write!(f, "invalid column number: {e}"),
+        }
+    }
+}
+
+impl core::error::Error for ParseLocationError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            ParseLocationError::InvalidLine(e) | ParseLocationError::InvalidColumn(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Parses the `file:line:column` format written by `Display`.
+///
+/// The line and column are taken from the last two `:` separators, so paths containing colons
+/// (such as Windows drive letters) are kept intact.
+impl FromStr for Location {
+    type Err = ParseLocationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.rsplitn(3, ':');
+        let column = parts.next().ok_or(ParseLocationError::MissingSeparator)?;
+        let line = parts.next().ok_or(ParseLocationError::MissingSeparator)?;
+        let file = parts.next().ok_or(ParseLocationError::MissingSeparator)?;
+        if file.is_empty() {
+            return Err(ParseLocationError::EmptyFile);
+        }
+        let line = line.parse().map_err(ParseLocationError::InvalidLine)?;
+        let column = column.parse().map_err(ParseLocationError::InvalidColumn)?;
+        Ok(Location::from_parts(file.to_string(), line, column))
+    }
+}
+
+impl TryFrom<&str> for Location {
+    type Error = ParseLocationError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(file: &str, line: u32, column: u32) -> Location {
+        Location::from_parts(file.to_string(), line, column)
+    }
+
+    #[cfg(not(locate_error_strip))]
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            "src/main.rs:40:19".parse(),
+            Ok(location("src/main.rs", 40, 19))
+        );
+        assert_eq!(
+            Location::try_from(r"C:\work\src\main.rs:3:5"),
+            Ok(location(r"C:\work\src\main.rs", 3, 5))
+        );
+        assert_eq!(
+            "/tmp/a:b/c.rs:1:2".parse(),
+            Ok(location("/tmp/a:b/c.rs", 1, 2))
+        );
+
+        // Round trips through `Display`
+        let original = Location::new();
+        assert_eq!(original.to_string().parse(), Ok(original));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "src/main.rs".parse::<Location>(),
+            Err(ParseLocationError::MissingSeparator)
+        );
+        assert_eq!(
+            "40:19".parse::<Location>(),
+            Err(ParseLocationError::MissingSeparator)
+        );
+        assert_eq!(
+            ":40:19".parse::<Location>(),
+            Err(ParseLocationError::EmptyFile)
+        );
+        assert!(matches!(
+            "src/main.rs:x:19".parse::<Location>(),
+            Err(ParseLocationError::InvalidLine(_))
+        ));
+        assert!(matches!(
+            "C:\\main.rs:19".parse::<Location>(),
+            Err(ParseLocationError::InvalidLine(_))
+        ));
+        assert!(matches!(
+            "src/main.rs:40:".parse::<Location>(),
+            Err(ParseLocationError::InvalidColumn(_))
+        ));
+    }
+}
@@ -0,0 +1,180 @@
+//! Normalisation of the file paths shown when displaying a [`Location`](crate::Location).
+//!
+//! Paths come from `core::panic::Location::file`, which may be absolute or relative to the
+//! directory the compiler was invoked from depending on how a crate was built. A [`PathRemap`]
+//! can be set globally with [`set_global`], which applies to `Display` of every `Location`, or
+//! given to a single report.
+
+use crate::Location;
+use crate::runtime::{self, Mode};
+use std::borrow::Cow;
+use std::path::Path;
+use std::sync::RwLock;
+
+/// Rules for shortening the file path of a location.
+///
+/// The first rule which matches a path is applied, checked in the order: registry collapsing,
+/// workspace root, then stripped prefixes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathRemap {
+    collapse_registry: bool,
+    workspace_root: Option<String>,
+    strip_prefixes: Vec<String>,
+}
+
+impl PathRemap {
+    /// A remap which leaves paths unchanged
+    pub const fn new() -> Self {
+        PathRemap {
+            collapse_registry: false,
+            workspace_root: None,
+            strip_prefixes: Vec::new(),
+        }
+    }
+
+    /// Removes `prefix` from the start of paths, may be given multiple times
+    pub fn strip_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.strip_prefixes.push(prefix.into());
+        self
+    }
+
+    /// Makes paths within `root` relative to it
+    pub fn workspace_root(mut self, root: impl Into<String>) -> Self {
+        self.workspace_root = Some(root.into());
+        self
+    }
+
+    /// Shortens paths in the cargo registry, e.g.
+    /// `/home/ci/.cargo/registry/src/index.crates.io-6f17d22bba15001f/serde-1.0.200/src/de.rs`
+    /// becomes `serde@1.0.200/src/de.rs`
+    pub fn collapse_registry(mut self, collapse: bool) -> Self {
+        self.collapse_registry = collapse;
+        self
+    }
+
+    pub fn apply<'a>(&self, path: &'a str) -> Cow<'a, str> {
+        if self.collapse_registry
+            && let Some(collapsed) = collapse_registry(path)
+        {
+            return Cow::Owned(collapsed);
+        }
+        if let Some(root) = &self.workspace_root
+            && let Ok(relative) = Path::new(path).strip_prefix(root)
+        {
+            return Cow::Owned(relative.to_string_lossy().into_owned());
+        }
+        for prefix in &self.strip_prefixes {
+            if let Some(stripped) = path.strip_prefix(prefix.as_str()) {
+                return Cow::Borrowed(stripped);
+            }
+        }
+        Cow::Borrowed(path)
+    }
+}
+
+/// Rewrites `.../registry/src/<index>/<name>-<version>/<rest>` to `<name>@<version>/<rest>`
+fn collapse_registry(path: &str) -> Option<String> {
+    let components: Vec<&str> = path.split(['/', '\\']).collect();
+    let registry = components
+        .windows(2)
+        .position(|pair| pair == ["registry", "src"])?;
+    // Skip `registry`, `src` and the index directory
+    let (package, rest) = components.get(registry + 3..)?.split_first()?;
+    // Versions start with a digit, crate names may contain `-` followed by digits (e.g. `sha-1`)
+    let split = package.match_indices('-').map(|(i, _)| i).find(|&i| {
+        let version = &package[i + 1..];
+        version.split('.').take(3).count() == 3
+            && version
+                .split('.')
+                .next()
+                .is_some_and(|major| !major.is_empty() && major.bytes().all(|b| b.is_ascii_digit()))
+    })?;
+    let (name, version) = (&package[..split], &package[split + 1..]);
+    Some(format!("{name}@{version}/{}", rest.join("/")))
+}
+
+static GLOBAL: RwLock<PathRemap> = RwLock::new(PathRemap::new());
+
+/// Sets the remap applied when displaying any `Location`
+pub fn set_global(remap: PathRemap) {
+    *GLOBAL.write().unwrap_or_else(|e| e.into_inner()) = remap;
+}
+
+/// Applies the global remap to `path`
+pub(crate) fn remap_global(path: &str) -> Cow<'_, str> {
+    GLOBAL.read().unwrap_or_else(|e| e.into_inner()).apply(path)
+}
+
+/// The file of a location as exported to other tools, remapped by the global remap or hashed when
+/// locations are redacted
+pub(crate) fn export(path: &str) -> String {
+    if runtime::mode() == Mode::Redact {
+        return runtime::hash_path(path);
+    }
+    remap_global(path).into_owned()
+}
+
+/// A copy of `location` with the file exported as by [`export`] and without any captured traces,
+/// `None` when locations are off
+pub(crate) fn export_location(location: &Location) -> Option<Location> {
+    (runtime::mode() != Mode::Off)
+        .then(|| Location::from_parts(export(location.file()), location.line(), location.column()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Location;
+
+    const REGISTRY_PATH: &str = "/home/ci/.cargo/registry/src/index.crates.io-6f17d22bba15001f/tracing-error-0.2.1/src/layer.rs";
+
+    #[test]
+    fn test_apply() {
+        let remap = PathRemap::new();
+        assert_eq!(remap.apply("/a/b.rs"), "/a/b.rs");
+
+        let remap = PathRemap::new()
+            .strip_prefix("/build/")
+            .strip_prefix("/other/");
+        assert_eq!(remap.apply("/build/src/main.rs"), "src/main.rs");
+        assert_eq!(remap.apply("/other/src/main.rs"), "src/main.rs");
+        assert_eq!(remap.apply("src/main.rs"), "src/main.rs");
+
+        let remap = PathRemap::new().workspace_root("/home/ci/project");
+        assert_eq!(remap.apply("/home/ci/project/src/main.rs"), "src/main.rs");
+        assert_eq!(
+            remap.apply("/home/ci/project2/src/main.rs"),
+            "/home/ci/project2/src/main.rs"
+        );
+
+        let remap = PathRemap::new().collapse_registry(true);
+        assert_eq!(
+            remap.apply(REGISTRY_PATH),
+            "tracing-error@0.2.1/src/layer.rs"
+        );
+        assert_eq!(
+            remap.apply(
+                r"C:\Users\ci\.cargo\registry\src\index.crates.io-1\sha-1-0.10.0-rc.1\src\lib.rs"
+            ),
+            "sha-1@0.10.0-rc.1/src/lib.rs"
+        );
+        assert_eq!(
+            remap.apply("/registry/src/index/src/main.rs"),
+            "/registry/src/index/src/main.rs"
+        );
+    }
+
+    #[cfg(not(locate_error_strip))]
+    #[test]
+    fn test_global() {
+        let location = Location::from_parts("/unique-remap-prefix/src/main.rs".to_string(), 1, 2);
+        assert_eq!(location.to_string(), "/unique-remap-prefix/src/main.rs:1:2");
+        set_global(PathRemap::new().strip_prefix("/unique-remap-prefix/"));
+        assert_eq!(location.to_string(), "src/main.rs:1:2");
+        assert_eq!(
+            location.display_with(&PathRemap::new()).to_string(),
+            "/unique-remap-prefix/src/main.rs:1:2"
+        );
+        set_global(PathRemap::new());
+    }
+}
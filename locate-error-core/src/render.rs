@@ -0,0 +1,409 @@
+//! Rendering of an error chain with the location of each error, as used by the report handlers
+
+use crate::format::LocationFormat;
+use crate::hyperlink::{self, Hyperlinks, Stream, UrlScheme};
+use crate::paths::PathRemap;
+use crate::permalink::Permalinks;
+use crate::runtime::{self, Mode};
+use crate::snippet::SourceSnippet;
+use crate::style::{ColorChoice, Theme};
+use crate::{Chain, Hop, Location};
+use core::error::Error;
+use core::fmt;
+
+/// How a rendered error chain is laid out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Layout {
+    /// The error followed by a numbered list of its causes, with the location of each
+    #[default]
+    Chain,
+    /// A `path:line:column: error: message` line per error, as parsed by editors such as Vim's
+    /// quickfix, Emacs' `compilation-mode` and VS Code problem matchers
+    Compiler,
+    /// As [`Layout::Compiler`], with a `note:` line after each located error naming the type it
+    /// was converted into
+    CompilerWithNotes,
+}
+
+/// Options for rendering an error chain.
+///
+/// Unset options fall back to the global settings used when displaying a `Location`.
+///
+/// ```text
+/// Outer message
+///     at src/main.rs:28:61
+///
+/// Caused by:
+///    0: Inner message
+///       at src/main.rs:40:19
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Renderer {
+    paths: Option<PathRemap>,
+    location_format: Option<LocationFormat>,
+    url_scheme: Option<UrlScheme>,
+    hyperlinks: Hyperlinks,
+    stream: Stream,
+    snippet_context: Option<usize>,
+    permalinks: Option<Permalinks>,
+    colors: ColorChoice,
+    theme: Theme,
+    layout: Layout,
+}
+
+impl Renderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shortens file paths with `remap` instead of the global remap
+    pub fn paths(mut self, remap: PathRemap) -> Self {
+        self.paths = Some(remap);
+        self
+    }
+
+    /// Writes locations in `format` instead of the global format
+    pub fn location_format(mut self, format: LocationFormat) -> Self {
+        self.location_format = Some(format);
+        self
+    }
+
+    /// Links each location to its source with `scheme` in place of the global scheme set with
+    /// [`hyperlink::set_global`], when hyperlinks are enabled
+    pub fn url_scheme(mut self, scheme: UrlScheme) -> Self {
+        self.url_scheme = Some(scheme);
+        self
+    }
+
+    /// Sets when locations are hyperlinked, by default only when the report's [`Stream`] is a
+    /// terminal. `Always` requires a scheme set with [`Renderer::url_scheme`] or
+    /// [`hyperlink::set_global`]
+    pub fn hyperlinks(mut self, hyperlinks: Hyperlinks) -> Self {
+        self.hyperlinks = hyperlinks;
+        self
+    }
+
+    /// Sets the stream the report is printed to, checked by [`Hyperlinks::Auto`], stderr by
+    /// default
+    pub fn stream(mut self, stream: Stream) -> Self {
+        self.stream = stream;
+        self
+    }
+
+    /// Shows the source line of each location with up to `context` lines before it, capped at
+    /// [`MAX_CONTEXT_LINES`](crate::snippet::MAX_CONTEXT_LINES). Locations whose files are not
+    /// present on disk (e.g. released binaries) are shown without a snippet.
+    pub fn snippets(mut self, context: usize) -> Self {
+        self.snippet_context = Some(context);
+        self
+    }
+
+    /// Shows each location as a link to its file at the commit the binary was built from, when
+    /// the file is within the repository
+    pub fn permalinks(mut self, permalinks: Permalinks) -> Self {
+        self.permalinks = Some(permalinks);
+        self
+    }
+
+    /// Sets when the report is coloured, by default never
+    pub fn colors(mut self, colors: ColorChoice) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// Styles the report with `theme` when colours are enabled, [`Theme::dark`] by default
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Sets the layout of the report, [`Layout::Chain`] by default
+    pub fn layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Displays `error` and its causes in the configured layout
+    pub fn render<'a>(&'a self, error: &'a (dyn Error + 'static)) -> Rendered<'a> {
+        Rendered {
+            renderer: self,
+            error,
+        }
+    }
+
+    fn write_chain(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        error: &(dyn Error + 'static),
+    ) -> fmt::Result {
+        let global;
+        let hyperlink = match &self.url_scheme {
+            Some(scheme) => Some(scheme),
+            None => {
+                global = hyperlink::global();
+                global.as_ref()
+            }
+        }
+        .filter(|_| self.hyperlinks.enabled(self.stream));
+        let theme = match self.colors.enabled() {
+            true => &self.theme,
+            false => &Theme::plain(),
+        };
+        let mut hops = Chain::new(error);
+        if let Some(hop) = hops.next() {
+            write!(f, "{}", theme.message.paint(hop.error()))?;
+            if let Some(location) = shown_location(&hop) {
+                self.write_location(f, location, hyperlink, theme, "    ")?;
+            }
+        }
+        for (i, hop) in hops.enumerate() {
+            if i == 0 {
+                write!(f, "\n\n{}", theme.keyword.paint("Caused by:"))?;
+            }
+            write!(f, "\n   {i}: {}", theme.cause.paint(hop.error()))?;
+            if let Some(location) = shown_location(&hop) {
+                self.write_location(f, location, hyperlink, theme, "      ")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a line per hop for [`Layout::Compiler`]. Locations are always written as
+    /// `path:line:column` without hyperlinks or colours so they can be parsed.
+    fn write_compiler(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        error: &(dyn Error + 'static),
+    ) -> fmt::Result {
+        let format = LocationFormat::WithColumn;
+        for (i, hop) in Chain::new(error).enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            // Keep each error on a single line
+            let message = hop.error().to_string();
+            let message = message
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let Some(location) = shown_location(&hop) else {
+                write!(f, "error: {message}")?;
+                continue;
+            };
+            let mut display = location.display().format(&format);
+            if let Some(remap) = &self.paths {
+                display = display.paths(remap);
+            }
+            write!(f, "{display}: error: {message}")?;
+            if self.layout == Layout::CompilerWithNotes
+                && let Some(type_name) = hop.type_name()
+            {
+                write!(f, "\n{display}: note: converted into `{type_name}` here")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the location of a hop, followed by its span trace and backtrace if captured
+    fn write_location(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        location: &Location,
+        hyperlink: Option<&UrlScheme>,
+        theme: &Theme,
+        indent: &str,
+    ) -> fmt::Result {
+        let mut display = location.display();
+        if let Some(remap) = &self.paths {
+            display = display.paths(remap);
+        }
+        if let Some(format) = &self.location_format {
+            display = display.format(format);
+        }
+        display = display.hyperlink(hyperlink);
+        write!(f, "\n{indent}{} ", theme.keyword.paint("at"))?;
+        // Redacted locations are not linked to their source
+        let revealed = runtime::mode() == Mode::On;
+        match self
+            .permalinks
+            .as_ref()
+            .filter(|_| revealed)
+            .and_then(|p| p.url(location))
+        {
+            Some(url) => write!(f, "{}", theme.path.paint(url))?,
+            None => write!(f, "{}", theme.path.paint(display))?,
+        }
+        if let Some(context) = self.snippet_context.filter(|_| revealed)
+            && let Some(snippet) = SourceSnippet::read(location, context)
+        {
+            let mut rendered = String::new();
+            snippet.write_styled(&mut rendered, theme.line_number, theme.caret)?;
+            for line in rendered.lines() {
+                write!(f, "\n{indent}{line}")?;
+            }
+        }
+        #[cfg(feature = "spantrace")]
+        if let Some(span_trace) = location.span_trace()
+            && span_trace.status() == tracing_error::SpanTraceStatus::CAPTURED
+        {
+            write!(f, "\n{indent}{}", theme.keyword.paint("span trace:"))?;
+            for line in span_trace.to_string().lines() {
+                write!(f, "\n{indent}{line}")?;
+            }
+        }
+        #[cfg(feature = "backtrace")]
+        if let Some(backtrace) = location.backtrace()
+            && backtrace.status() == std::backtrace::BacktraceStatus::Captured
+        {
+            write!(f, "\n{indent}{}", theme.keyword.paint("stack backtrace:"))?;
+            for line in backtrace.to_string().lines() {
+                write!(f, "\n{indent}{line}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The location of `hop`, unless locations are off
+fn shown_location<'a>(hop: &Hop<'a>) -> Option<&'a Location> {
+    hop.location().filter(|_| runtime::mode() != Mode::Off)
+}
+
+/// An error chain displayed by a [`Renderer`]
+#[derive(Clone, Copy)]
+pub struct Rendered<'a> {
+    renderer: &'a Renderer,
+    error: &'a (dyn Error + 'static),
+}
+
+impl fmt::Display for Rendered<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.renderer.layout {
+            Layout::Chain => self.renderer.write_chain(f, self.error),
+            Layout::Compiler | Layout::CompilerWithNotes => {
+                self.renderer.write_compiler(f, self.error)
+            }
+        }
+    }
+}
+
+#[cfg(all(test, not(locate_error_strip)))]
+mod tests {
+    use super::*;
+    use crate::fixtures::Outer;
+
+    #[test]
+    fn test_render() {
+        let outer = Outer::at(Location::from_parts("/build/src/main.rs".to_string(), 1, 2));
+        assert_eq!(
+            Renderer::new().render(&outer).to_string(),
+            "outer\n    at /build/src/main.rs:1:2\n\nCaused by:\n   0: inner"
+        );
+
+        let renderer = Renderer::new()
+            .paths(PathRemap::new().strip_prefix("/build/"))
+            .location_format(LocationFormat::Compact);
+        assert_eq!(
+            renderer.render(&outer).to_string(),
+            "outer\n    at src/main.rs:1\n\nCaused by:\n   0: inner"
+        );
+
+        let renderer = Renderer::new()
+            .location_format(LocationFormat::Compact)
+            .url_scheme(UrlScheme::File)
+            .hyperlinks(Hyperlinks::Always);
+        assert_eq!(
+            renderer.render(&outer).to_string(),
+            "outer\n    at \x1b]8;;file:///build/src/main.rs\x1b\\/build/src/main.rs:1\x1b]8;;\x1b\\\n\nCaused by:\n   0: inner"
+        );
+        let renderer = renderer.hyperlinks(Hyperlinks::Never);
+        assert!(!renderer.render(&outer).to_string().contains('\x1b'));
+    }
+
+    #[test]
+    fn test_compiler_layout() {
+        let outer = Outer::at(Location::from_parts("/build/src/main.rs".to_string(), 1, 2));
+        let renderer = Renderer::new()
+            .paths(PathRemap::new().strip_prefix("/build/"))
+            .location_format(LocationFormat::Rustc)
+            .layout(Layout::Compiler);
+        assert_eq!(
+            renderer.render(&outer).to_string(),
+            "src/main.rs:1:2: error: outer\nerror: inner"
+        );
+
+        let renderer = renderer.layout(Layout::CompilerWithNotes);
+        assert_eq!(
+            renderer.render(&outer).to_string(),
+            format!(
+                "src/main.rs:1:2: error: outer\nsrc/main.rs:1:2: note: converted into `{}` here\nerror: inner",
+                core::any::type_name::<Outer>()
+            )
+        );
+    }
+
+    #[test]
+    fn test_colors() {
+        let outer = Outer::at(Location::from_parts("src/main.rs".to_string(), 1, 2));
+        let renderer = Renderer::new().colors(ColorChoice::Always);
+        assert_eq!(
+            renderer.render(&outer).to_string(),
+            "\x1b[1;91mouter\x1b[0m\n    \x1b[90mat\x1b[0m \x1b[96msrc/main.rs:1:2\x1b[0m\n\n\x1b[90mCaused by:\x1b[0m\n   0: \x1b[33minner\x1b[0m"
+        );
+
+        let renderer = renderer.theme(Theme::plain());
+        assert_eq!(
+            renderer.render(&outer).to_string(),
+            Renderer::new().render(&outer).to_string()
+        );
+    }
+
+    #[test]
+    fn test_permalinks() {
+        let renderer =
+            Renderer::new().permalinks(Permalinks::new("https://github.com/org/repo", "abc123"));
+        let outer = Outer::at(Location::from_parts("src/main.rs".to_string(), 1, 2));
+        assert_eq!(
+            renderer.render(&outer).to_string(),
+            "outer\n    at https://github.com/org/repo/blob/abc123/src/main.rs#L1\n\nCaused by:\n   0: inner"
+        );
+
+        // Falls back to the location outside the repository
+        let outer = Outer::at(Location::from_parts("/build/src/main.rs".to_string(), 1, 2));
+        assert_eq!(
+            renderer.render(&outer).to_string(),
+            "outer\n    at /build/src/main.rs:1:2\n\nCaused by:\n   0: inner"
+        );
+    }
+
+    #[test]
+    fn test_snippets() {
+        // Points at `column!()` in this file, shown with the line before it
+        let (line, column) = (line!(), column!());
+        let file = concat!(env!("CARGO_MANIFEST_DIR"), "/src/render.rs");
+        let location = Location::from_parts(file.to_string(), line, column);
+        let snippet = SourceSnippet::read(&location, 1).unwrap().to_string();
+        assert!(snippet.contains("shown with the line before it"));
+        let snippet: Vec<_> = snippet.lines().map(|line| format!("    {line}")).collect();
+        let renderer = Renderer::new()
+            .location_format(LocationFormat::Compact)
+            .snippets(1);
+        assert_eq!(
+            renderer.render(&Outer::at(location)).to_string(),
+            format!(
+                "outer\n    at {file}:{line}\n{}\n\nCaused by:\n   0: inner",
+                snippet.join("\n")
+            )
+        );
+
+        // Missing files are skipped
+        let outer = Outer::at(Location::from_parts("missing/main.rs".to_string(), 8, 12));
+        assert_eq!(
+            renderer.render(&outer).to_string(),
+            "outer\n    at missing/main.rs:8\n\nCaused by:\n   0: inner"
+        );
+    }
+}
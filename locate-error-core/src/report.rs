@@ -0,0 +1,205 @@
+//! An error report for returning from `main`.
+
+use crate::__private::{self, BoxedError, Caller, caller};
+use crate::render::Renderer;
+use crate::{Chain, Located, Location, as_located, register};
+use core::error::Error;
+use core::fmt;
+use std::sync::Once;
+
+/// Any error along with the location it was converted into a report, for use as the error type
+/// of `main` or other application code.
+///
+/// `Debug` renders the error with its causes and the location of each, as printed when `main`
+/// returns an error:
+///
+/// ```text
+/// Error: failed to load config
+///     at src/main.rs:12:37
+///
+/// Caused by:
+///    0: No such file or directory (os error 2)
+/// ```
+///
+/// Like other error reports, `Report` doesn't implement `Error` itself, so that every error
+/// can be converted into it with `?`.
+pub struct Report {
+    error: ReportError,
+}
+
+/// The converted error with the location of the conversion, shown in place of the error's own
+/// location when it has none. Errors returned from `main` need not be `Send` or `Sync`.
+struct ReportError<E: ?Sized + BoxedError = dyn Error + Send + Sync + 'static> {
+    location: Location,
+    error: Box<E>,
+}
+
+impl Report {
+    #[cfg_attr(not(locate_error_strip), track_caller)]
+    pub fn new<E: Error + Send + Sync + 'static>(error: E) -> Self {
+        static REGISTER: Once = Once::new();
+        REGISTER.call_once(register::<ReportError>);
+        Report {
+            error: ReportError::new::<E>(Box::new(error), caller!()),
+        }
+    }
+
+    /// Location where the error was converted into the report
+    pub fn location(&self) -> &Location {
+        &self.error.location
+    }
+
+    /// The converted error
+    pub fn error(&self) -> &(dyn Error + Send + Sync + 'static) {
+        &*self.error.error
+    }
+
+    /// The converted error followed by its causes
+    pub fn chain(&self) -> Chain<'_> {
+        Chain::new(self.error())
+    }
+}
+
+impl<E: ?Sized + BoxedError + 'static> ReportError<E> {
+    /// Locates `error`, converted from an `S`, at `caller`
+    fn new<S>(error: Box<E>, caller: Caller) -> Self {
+        let converted = error.as_error();
+        let first_hop = as_located(converted)
+            .and_then(|located| located.location())
+            .is_none();
+        let location =
+            __private::locate::<S, Report>(caller, first_hop, Some(&converted), Some(converted));
+        ReportError { location, error }
+    }
+
+    /// The error as shown in the report, located at the conversion unless it has its own location
+    fn shown(&self) -> &(dyn Error + 'static) {
+        let error = self.error.as_error();
+        match as_located(error).and_then(|located| located.location()) {
+            Some(_) => error,
+            None => self,
+        }
+    }
+}
+
+/// Prints an error of type `S` returned from `main` as a report, located at `caller` where `main`
+/// returned it unless it has its own location
+pub(crate) fn eprint_main_error<S>(error: Box<dyn Error + 'static>, caller: Caller) {
+    register::<ReportError<dyn Error>>();
+    let report = ReportError::new::<S>(error, caller);
+    eprintln!("Error: {}", Renderer::new().render(report.shown()));
+}
+
+impl<E: Error + Send + Sync + 'static> From<E> for Report {
+    #[cfg_attr(not(locate_error_strip), track_caller)]
+    fn from(error: E) -> Self {
+        Report::new(error)
+    }
+}
+
+impl fmt::Debug for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Renderer::new().render(self.error.shown()))
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error.error)
+    }
+}
+
+impl From<Report> for Box<dyn Error + Send + Sync + 'static> {
+    fn from(report: Report) -> Self {
+        report.error.error
+    }
+}
+
+impl<E: ?Sized + BoxedError> fmt::Debug for ReportError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.error.as_error(), f)
+    }
+}
+
+impl<E: ?Sized + BoxedError> fmt::Display for ReportError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.error.as_error(), f)
+    }
+}
+
+impl<E: ?Sized + BoxedError> Error for ReportError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.error.as_error().source()
+    }
+}
+
+impl<E: ?Sized + BoxedError> Located for ReportError<E> {
+    fn location(&self) -> Option<&Location> {
+        Some(&self.location)
+    }
+
+    /// The next error in the converted error's chain, as the converted error is shown in place
+    fn located_source(&self) -> Option<&(dyn Error + 'static)> {
+        let error = self.error.as_error();
+        as_located(error)
+            .and_then(|located| located.located_source())
+            .or_else(|| error.source())
+    }
+
+    fn type_name(&self) -> &'static str {
+        core::any::type_name::<Report>()
+    }
+}
+
+#[cfg(all(test, not(locate_error_strip)))]
+mod tests {
+    use super::*;
+    use crate::fixtures::{Inner, Outer};
+
+    #[derive(Debug)]
+    struct Config(Inner);
+
+    impl fmt::Display for Config {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "failed to load config")
+        }
+    }
+
+    impl Error for Config {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    fn load() -> Result<(), Report> {
+        Err(Config(Inner))?
+    }
+
+    #[test]
+    fn test_unlocated() {
+        let (line, report) = (line!(), load().unwrap_err());
+        assert_eq!(report.location().file(), file!());
+        assert_eq!(report.location().line(), line - 5);
+        assert_eq!(report.to_string(), "failed to load config");
+        // Followed by any traces captured on the first hop
+        let debug = format!("{report:?}");
+        assert!(debug.starts_with(&format!(
+            "failed to load config\n    at {}\n",
+            report.location()
+        )));
+        assert!(debug.ends_with("\n\nCaused by:\n   0: inner"));
+        assert_eq!(report.chain().count(), 2);
+    }
+
+    #[test]
+    fn test_located() {
+        let outer = Outer::at(Location::from_parts("src/main.rs".to_string(), 40, 19));
+        let report = Report::from(outer);
+        assert_eq!(report.location().line(), line!() - 1);
+        // The error's own location is shown rather than the conversion into the report
+        assert_eq!(
+            format!("{report:?}"),
+            "outer\n    at src/main.rs:40:19\n\nCaused by:\n   0: inner"
+        );
+    }
+}
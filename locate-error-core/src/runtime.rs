@@ -0,0 +1,132 @@
+//! Runtime control of location capture and display, without rebuilding.
+//!
+//! The mode is read from the `LOCATE_ERROR` environment variable (`on`, `redact` or `off`) the
+//! first time it is needed, and can be changed at any time with [`set_mode`]. Unlike stripping
+//! locations at compile time, file paths are still compiled into the binary.
+
+use crate::fingerprint::Fnv1a;
+use core::fmt;
+use core::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Whether locations are captured and how they are displayed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Locations are captured and displayed in full
+    #[default]
+    On,
+    /// Locations are captured, but displayed with the file replaced by its [`hash_path`] and
+    /// without hyperlinks, permalinks or source snippets
+    Redact,
+    /// Locations are not captured, conversions are not counted or reported to the hook, and
+    /// `Display` writes nothing
+    Off,
+}
+
+impl FromStr for Mode {
+    type Err = ParseModeError;
+
+    /// Parses `on`, `redact` or `off`, ignoring case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "on" => Ok(Mode::On),
+            "redact" => Ok(Mode::Redact),
+            "off" => Ok(Mode::Off),
+            _ => Err(ParseModeError),
+        }
+    }
+}
+
+/// Error parsing a [`Mode`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseModeError;
+
+impl fmt::Display for ParseModeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected one of `on`, `redact` or `off`")
+    }
+}
+
+impl core::error::Error for ParseModeError {}
+
+/// Not yet read from the environment
+const UNINIT: u8 = u8::MAX;
+
+static MODE: AtomicU8 = AtomicU8::new(UNINIT);
+
+fn encode(mode: Mode) -> u8 {
+    match mode {
+        Mode::On => 0,
+        Mode::Redact => 1,
+        Mode::Off => 2,
+    }
+}
+
+fn decode(value: u8) -> Mode {
+    match value {
+        1 => Mode::Redact,
+        2 => Mode::Off,
+        _ => Mode::On,
+    }
+}
+
+/// The current mode, initialised from `LOCATE_ERROR` on first use. Unset or unrecognised values
+/// leave locations [`Mode::On`].
+pub fn mode() -> Mode {
+    match MODE.load(Ordering::Relaxed) {
+        UNINIT => {
+            let mode = std::env::var("LOCATE_ERROR")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_default();
+            // Keep a mode set concurrently with `set_mode`
+            match MODE.compare_exchange(UNINIT, encode(mode), Ordering::Relaxed, Ordering::Relaxed)
+            {
+                Ok(_) => mode,
+                Err(current) => decode(current),
+            }
+        }
+        value => decode(value),
+    }
+}
+
+/// Sets the mode, overriding `LOCATE_ERROR`
+pub fn set_mode(mode: Mode) {
+    MODE.store(encode(mode), Ordering::Relaxed);
+}
+
+/// Hashes a file path for [`Mode::Redact`].
+///
+/// The hash is the 64 bit FNV-1a hash of the UTF-8 path written as 16 lowercase hex digits. It
+/// does not depend on the platform or the version of this crate, so redacted paths can be matched
+/// against the hashes of known source files.
+pub fn hash_path(path: &str) -> String {
+    let mut hash = Fnv1a::new();
+    hash.write(path.as_bytes());
+    format!("{:016x}", hash.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("on".parse(), Ok(Mode::On));
+        assert_eq!(" Redact\n".parse(), Ok(Mode::Redact));
+        assert_eq!("OFF".parse(), Ok(Mode::Off));
+        assert_eq!("hidden".parse::<Mode>(), Err(ParseModeError));
+        for mode in [Mode::On, Mode::Redact, Mode::Off] {
+            assert_eq!(decode(encode(mode)), mode);
+        }
+    }
+
+    #[test]
+    fn test_hash_path() {
+        // Reference values of 64 bit FNV-1a
+        assert_eq!(hash_path(""), "cbf29ce484222325");
+        assert_eq!(hash_path("a"), "af63dc4c8601ec8c");
+        assert_eq!(hash_path("src/main.rs"), hash_path("src/main.rs"));
+        assert_ne!(hash_path("src/main.rs"), hash_path("src/lib.rs"));
+    }
+}
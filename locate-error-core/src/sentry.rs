@@ -0,0 +1,202 @@
+//! Sentry event payloads built from located error chains.
+//!
+//! Only the JSON payload is built, so events can be sent with any transport. Each error in the
+//! chain becomes an entry of `exception.values`, ordered from the innermost cause to the outermost
+//! error as Sentry expects, with a stack frame at the error's location.
+
+use crate::fingerprint::{Fingerprint, Fingerprinter};
+use crate::runtime::{self, Mode};
+use crate::{Chain, Location, Snapshot, paths};
+use core::error::Error;
+use serde_json::{Map, Value, json};
+
+/// Builds Sentry event payloads.
+///
+/// ```json
+/// {
+///   "level": "error",
+///   "platform": "native",
+///   "exception": {
+///     "values": [
+///       { "value": "alice not found" },
+///       {
+///         "type": "app::Error",
+///         "value": "no account for alice",
+///         "stacktrace": {
+///           "frames": [
+///             { "filename": "src/main.rs", "lineno": 40, "colno": 19, "in_app": true }
+///           ]
+///         }
+///       }
+///     ]
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EventBuilder {
+    in_app_prefixes: Vec<String>,
+    fingerprint: bool,
+}
+
+impl EventBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks frames whose file starts with `prefix` as application code, may be given multiple
+    /// times. Without any prefixes, frames with relative paths are marked as application code, as
+    /// rustc gives crates in the workspace relative paths and dependencies absolute ones.
+    ///
+    /// Files are matched as captured when building from an error, and as exported, remapped or
+    /// hashed, when building from a [`Snapshot`].
+    pub fn in_app_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.in_app_prefixes.push(prefix.into());
+        self
+    }
+
+    /// Sets the event's `fingerprint` to the [`Fingerprint`] of the chain, replacing Sentry's own
+    /// grouping. Off by default.
+    pub fn fingerprint(mut self, fingerprint: bool) -> Self {
+        self.fingerprint = fingerprint;
+        self
+    }
+
+    /// Builds the payload of an event for an error and its sources
+    pub fn build(&self, error: &(dyn Error + 'static)) -> Value {
+        let mut values: Vec<_> = Chain::new(error)
+            .map(|hop| {
+                let frame = hop
+                    .location()
+                    .filter(|_| runtime::mode() != Mode::Off)
+                    .map(|location| self.frame(&paths::export(location.file()), location));
+                exception(hop.type_name(), &hop.error().to_string(), frame)
+            })
+            .collect();
+        values.reverse();
+        let fingerprint = self.fingerprint.then(|| Fingerprinter::new().error(error));
+        event(values, fingerprint)
+    }
+
+    /// Builds the payload of an event for a snapshot of an error chain, whose files are already
+    /// exported
+    pub fn build_snapshot(&self, snapshot: &Snapshot) -> Value {
+        let values = snapshot
+            .hops
+            .iter()
+            .rev()
+            .map(|hop| {
+                let frame = hop
+                    .location
+                    .as_ref()
+                    .map(|location| self.frame(location.file(), location));
+                exception(hop.type_name.as_deref(), &hop.message, frame)
+            })
+            .collect();
+        event(values, self.fingerprint.then(|| snapshot.fingerprint()))
+    }
+
+    /// A frame at `location`, whose file is written as `filename`
+    fn frame(&self, filename: &str, location: &Location) -> Value {
+        json!({
+            "filename": filename,
+            "lineno": location.line(),
+            "colno": location.column(),
+            "in_app": self.in_app(location.file()),
+        })
+    }
+
+    fn in_app(&self, file: &str) -> bool {
+        match self.in_app_prefixes.is_empty() {
+            true => !std::path::Path::new(file).is_absolute(),
+            false => self
+                .in_app_prefixes
+                .iter()
+                .any(|prefix| file.starts_with(prefix.as_str())),
+        }
+    }
+}
+
+fn exception(type_name: Option<&str>, message: &str, frame: Option<Value>) -> Value {
+    let mut exception = Map::new();
+    if let Some(type_name) = type_name {
+        exception.insert("type".to_string(), json!(type_name));
+    }
+    exception.insert("value".to_string(), json!(message));
+    if let Some(frame) = frame {
+        exception.insert("stacktrace".to_string(), json!({ "frames": [frame] }));
+    }
+    Value::Object(exception)
+}
+
+fn event(values: Vec<Value>, fingerprint: Option<Fingerprint>) -> Value {
+    let mut event = json!({
+        "level": "error",
+        "platform": "native",
+        "exception": { "values": values },
+    });
+    if let Some(fingerprint) = fingerprint {
+        event["fingerprint"] = json!([fingerprint.to_string()]);
+    }
+    event
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::Outer;
+
+    #[cfg(not(locate_error_strip))]
+    #[test]
+    fn test_build() {
+        let outer = Outer::at(Location::from_parts("src/main.rs".to_string(), 40, 19));
+        let payload = EventBuilder::new().build(&outer);
+        assert_eq!(
+            payload,
+            json!({
+                "level": "error",
+                "platform": "native",
+                "exception": {
+                    "values": [
+                        { "value": "inner" },
+                        {
+                            "type": core::any::type_name::<Outer>(),
+                            "value": "outer",
+                            "stacktrace": {
+                                "frames": [
+                                    {
+                                        "filename": "src/main.rs",
+                                        "lineno": 40,
+                                        "colno": 19,
+                                        "in_app": true
+                                    }
+                                ]
+                            }
+                        }
+                    ]
+                }
+            })
+        );
+
+        let payload = EventBuilder::new().fingerprint(true).build(&outer);
+        assert_eq!(
+            payload["fingerprint"],
+            json!([Snapshot::new(&outer).fingerprint().to_string()])
+        );
+    }
+
+    #[test]
+    fn test_in_app() {
+        let builder = EventBuilder::new();
+        assert!(builder.in_app("src/main.rs"));
+        assert!(!builder.in_app(
+            "/home/ci/.cargo/registry/src/index.crates.io-6f17d22bba15001f/serde-1.0.200/src/de.rs"
+        ));
+
+        let builder = EventBuilder::new()
+            .in_app_prefix("/build/app/")
+            .in_app_prefix("crates/");
+        assert!(builder.in_app("/build/app/src/main.rs"));
+        assert!(builder.in_app("crates/db/src/lib.rs"));
+        assert!(!builder.in_app("src/main.rs"));
+    }
+}
@@ -0,0 +1,145 @@
+use crate::{Chain, Hop, Location, paths};
+use core::error::Error;
+
+/// Owned copy of an error chain, recording the message, type name and location of each error.
+///
+/// With the `serde` feature this can be serialized to log a located chain structurally and
+/// deserialized on the other side.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    /// The error followed by its causes
+    pub hops: Vec<SnapshotHop>,
+}
+
+/// A single error in a [`Snapshot`]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnapshotHop {
+    /// `Display` of the error
+    pub message: String,
+    /// Type name of the error, known only for registered `Located` types
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub type_name: Option<String>,
+    /// Location of the error, with the file remapped or hashed as when it is displayed. Left out
+    /// when locations are off
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub location: Option<Location>,
+    /// Rendered span trace, if captured at this location
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub span_trace: Option<String>,
+    /// Rendered backtrace, if captured at this location
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub backtrace: Option<String>,
+}
+
+impl Snapshot {
+    pub fn new(error: &(dyn Error + 'static)) -> Self {
+        Snapshot {
+            hops: Chain::new(error).map(SnapshotHop::new).collect(),
+        }
+    }
+}
+
+impl SnapshotHop {
+    fn new(hop: Hop<'_>) -> Self {
+        let location = hop.location();
+        SnapshotHop {
+            message: hop.error().to_string(),
+            type_name: hop.type_name().map(str::to_string),
+            location: location.and_then(paths::export_location),
+            span_trace: location.and_then(span_trace),
+            backtrace: location.and_then(backtrace),
+        }
+    }
+}
+
+#[cfg(feature = "spantrace")]
+fn span_trace(location: &Location) -> Option<String> {
+    location
+        .span_trace()
+        .filter(|span_trace| span_trace.status() == tracing_error::SpanTraceStatus::CAPTURED)
+        .map(ToString::to_string)
+}
+
+#[cfg(not(feature = "spantrace"))]
+fn span_trace(_: &Location) -> Option<String> {
+    None
+}
+
+#[cfg(feature = "backtrace")]
+fn backtrace(location: &Location) -> Option<String> {
+    location
+        .backtrace()
+        .filter(|backtrace| backtrace.status() == std::backtrace::BacktraceStatus::Captured)
+        .map(ToString::to_string)
+}
+
+#[cfg(not(feature = "backtrace"))]
+fn backtrace(_: &Location) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::Outer;
+
+    #[test]
+    fn test_snapshot() {
+        let outer = Outer::new();
+        let snapshot = Snapshot::new(&outer);
+        assert_eq!(snapshot.hops.len(), 2);
+
+        let hop = &snapshot.hops[0];
+        assert_eq!(hop.message, "outer");
+        assert!(hop.type_name.as_ref().unwrap().ends_with("Outer"));
+        assert_eq!(hop.location.as_ref().unwrap(), &outer.location);
+
+        let hop = &snapshot.hops[1];
+        assert_eq!(hop.message, "inner");
+        assert!(hop.type_name.is_none());
+        assert!(hop.location.is_none());
+    }
+
+    #[cfg(all(feature = "serde", not(locate_error_strip)))]
+    #[test]
+    fn test_serde() {
+        let outer = Outer::new();
+        let json = serde_json::to_value(Snapshot::new(&outer)).unwrap();
+        assert_eq!(
+            json["hops"][0]["location"],
+            serde_json::json!({
+                "file": outer.location.file(),
+                "line": outer.location.line(),
+                "column": outer.location.column(),
+            })
+        );
+        assert_eq!(json["hops"][1], serde_json::json!({ "message": "inner" }));
+
+        let snapshot: Snapshot = serde_json::from_value(json).unwrap();
+        assert_eq!(snapshot.hops.len(), 2);
+        assert_eq!(snapshot.hops[0].message, "outer");
+        assert!(
+            snapshot.hops[0]
+                .type_name
+                .as_ref()
+                .unwrap()
+                .ends_with("Outer")
+        );
+        assert_eq!(snapshot.hops[0].location.as_ref().unwrap(), &outer.location);
+        assert!(snapshot.hops[1].location.is_none());
+    }
+}
@@ -0,0 +1,152 @@
+//! Rustc style excerpts of the source around a [`Location`].
+//!
+//! ```text
+//!    |
+//! 39 |     let config = load(path)?;
+//! 40 |     let port = config.port.parse()?;
+//!    |                                   ^
+//! ```
+//!
+//! Files are read relative to the current directory, so snippets are usually only available when
+//! running from the workspace which built the binary.
+
+use crate::Location;
+use crate::style::Style;
+use core::fmt;
+
+/// The most context lines shown before the located line
+pub const MAX_CONTEXT_LINES: usize = 10;
+
+/// The located line of a source file with up to `context` lines before it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceSnippet {
+    /// Line number of the first entry in `lines`
+    first_line: u32,
+    lines: Vec<String>,
+    column: u32,
+}
+
+impl SourceSnippet {
+    /// Reads the snippet for `location` with `context` lines before the located line, capped at
+    /// [`MAX_CONTEXT_LINES`]. Returns `None` if the file can't be read or is shorter than expected.
+    pub fn read(location: &Location, context: usize) -> Option<Self> {
+        let source = std::fs::read_to_string(location.file()).ok()?;
+        Self::from_source(&source, location.line(), location.column(), context)
+    }
+
+    /// Builds the snippet from the contents of the file
+    pub fn from_source(source: &str, line: u32, column: u32, context: usize) -> Option<Self> {
+        let index = usize::try_from(line).ok()?.checked_sub(1)?;
+        let start = index.saturating_sub(context.min(MAX_CONTEXT_LINES));
+        let lines: Vec<String> = source
+            .lines()
+            .skip(start)
+            .take(index - start + 1)
+            .map(str::to_string)
+            .collect();
+        if lines.len() != index - start + 1 {
+            return None;
+        }
+        Some(SourceSnippet {
+            first_line: u32::try_from(start).ok()? + 1,
+            lines,
+            column,
+        })
+    }
+}
+
+impl SourceSnippet {
+    /// Writes the snippet with the line numbers and gutter in `gutter` and the caret in `caret`
+    pub(crate) fn write_styled(
+        &self,
+        f: &mut dyn fmt::Write,
+        gutter: Style,
+        caret: Style,
+    ) -> fmt::Result {
+        let last_line = self.first_line as usize + self.lines.len() - 1;
+        let width = last_line.to_string().len();
+        let empty = format!("{:width$} |", "");
+        write!(f, "{}", gutter.paint(&empty))?;
+        for (number, text) in (self.first_line as usize..).zip(&self.lines) {
+            let number = format!("{number:>width$} |");
+            write!(f, "\n{} {text}", gutter.paint(number))?;
+        }
+        // Keep tabs so the caret lines up with the located line
+        let located = self.lines.last().map(String::as_str).unwrap_or_default();
+        write!(f, "\n{} ", gutter.paint(&empty))?;
+        for c in located
+            .chars()
+            .take((self.column as usize).saturating_sub(1))
+        {
+            f.write_str(if c == '\t' { "\t" } else { " " })?;
+        }
+        write!(f, "{}", caret.paint('^'))
+    }
+}
+
+impl fmt::Display for SourceSnippet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_styled(f, Style::new(), Style::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "fn main() {\n\tlet x = 1;\n    run(x)?;\n}\n";
+
+    #[test]
+    fn test_snippet() {
+        let snippet = SourceSnippet::from_source(SOURCE, 3, 9, 1).unwrap();
+        assert_eq!(
+            snippet.to_string(),
+            "  |\n2 | \tlet x = 1;\n3 |     run(x)?;\n  |         ^"
+        );
+
+        let snippet = SourceSnippet::from_source(SOURCE, 2, 2, 5).unwrap();
+        assert_eq!(
+            snippet.to_string(),
+            "  |\n1 | fn main() {\n2 | \tlet x = 1;\n  | \t^"
+        );
+
+        let source: String = (1..=20).map(|i| format!("{i}\n")).collect();
+        let snippet = SourceSnippet::from_source(&source, 20, 1, 100).unwrap();
+        assert_eq!(snippet.lines.len(), MAX_CONTEXT_LINES + 1);
+        assert!(snippet.to_string().starts_with("   |\n10 | 10\n"));
+
+        let mut styled = String::new();
+        SourceSnippet::from_source(SOURCE, 1, 4, 0)
+            .unwrap()
+            .write_styled(&mut styled, Style::new().bold(), Style::new().dimmed())
+            .unwrap();
+        assert_eq!(
+            styled,
+            "\x1b[1m  |\x1b[0m\n\x1b[1m1 |\x1b[0m fn main() {\n\x1b[1m  |\x1b[0m    \x1b[2m^\x1b[0m"
+        );
+
+        assert_eq!(SourceSnippet::from_source(SOURCE, 5, 1, 1), None);
+        assert_eq!(SourceSnippet::from_source(SOURCE, 0, 1, 1), None);
+    }
+
+    #[cfg(not(locate_error_strip))]
+    #[test]
+    fn test_read() {
+        // Points at `column!()` in this file
+        let (line, column) = (line!(), column!());
+        let file = concat!(env!("CARGO_MANIFEST_DIR"), "/src/snippet.rs");
+        let location = Location::from_parts(file.to_string(), line, column);
+        let snippet = SourceSnippet::read(&location, 0).unwrap();
+        let gutter = " ".repeat(line.to_string().len());
+        let caret = " ".repeat(column as usize - 1);
+        assert_eq!(
+            snippet.to_string(),
+            format!(
+                "{gutter} |\n{line} |         let (line, column) = (line!(), column!());\n{gutter} | {caret}^"
+            )
+        );
+
+        let missing = Location::from_parts("missing/main.rs".to_string(), 1, 1);
+        assert_eq!(SourceSnippet::read(&missing, 2), None);
+    }
+}
@@ -0,0 +1,305 @@
+//! ANSI colours for rendered reports.
+//!
+//! Colours are chosen with a [`ColorChoice`], which by default for a
+//! [`Renderer`](crate::render::Renderer) is `Never`. `Auto` colours output when stderr, where
+//! reports are usually written, is a terminal, and follows the
+//! [`NO_COLOR`](https://no-color.org) and [`CLICOLOR_FORCE`](https://bixense.com/clicolors/)
+//! conventions.
+
+use core::fmt;
+use std::io::IsTerminal;
+
+/// One of the 16 standard terminal colours, or an index into the 256 colour palette
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    Fixed(u8),
+}
+
+impl Color {
+    fn write_foreground(self, f: &mut dyn fmt::Write) -> fmt::Result {
+        let code = match self {
+            Color::Black => 30,
+            Color::Red => 31,
+            Color::Green => 32,
+            Color::Yellow => 33,
+            Color::Blue => 34,
+            Color::Magenta => 35,
+            Color::Cyan => 36,
+            Color::White => 37,
+            Color::BrightBlack => 90,
+            Color::BrightRed => 91,
+            Color::BrightGreen => 92,
+            Color::BrightYellow => 93,
+            Color::BrightBlue => 94,
+            Color::BrightMagenta => 95,
+            Color::BrightCyan => 96,
+            Color::BrightWhite => 97,
+            Color::Fixed(index) => return write!(f, "38;5;{index}"),
+        };
+        write!(f, "{code}")
+    }
+}
+
+/// A foreground colour and text attributes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Style {
+    foreground: Option<Color>,
+    bold: bool,
+    dimmed: bool,
+    italic: bool,
+    underline: bool,
+}
+
+impl Style {
+    /// Unstyled text
+    pub const fn new() -> Self {
+        Style {
+            foreground: None,
+            bold: false,
+            dimmed: false,
+            italic: false,
+            underline: false,
+        }
+    }
+
+    pub const fn fg(mut self, color: Color) -> Self {
+        self.foreground = Some(color);
+        self
+    }
+
+    pub const fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub const fn dimmed(mut self) -> Self {
+        self.dimmed = true;
+        self
+    }
+
+    pub const fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    pub const fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    pub fn is_plain(&self) -> bool {
+        *self == Style::new()
+    }
+
+    /// Displays `value` in this style
+    pub fn paint<T: fmt::Display>(self, value: T) -> Painted<T> {
+        Painted { style: self, value }
+    }
+
+    fn write_prefix(&self, f: &mut dyn fmt::Write) -> fmt::Result {
+        f.write_str("\x1b[")?;
+        let mut first = true;
+        let mut separate = |f: &mut dyn fmt::Write| {
+            let result = if first { Ok(()) } else { f.write_char(';') };
+            first = false;
+            result
+        };
+        for (enabled, code) in [
+            (self.bold, "1"),
+            (self.dimmed, "2"),
+            (self.italic, "3"),
+            (self.underline, "4"),
+        ] {
+            if enabled {
+                separate(f)?;
+                f.write_str(code)?;
+            }
+        }
+        if let Some(color) = self.foreground {
+            separate(f)?;
+            color.write_foreground(f)?;
+        }
+        f.write_char('m')
+    }
+}
+
+/// A value displayed in a [`Style`], see [`Style::paint`]
+#[derive(Debug, Clone, Copy)]
+pub struct Painted<T> {
+    style: Style,
+    value: T,
+}
+
+impl<T: fmt::Display> fmt::Display for Painted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.style.is_plain() {
+            return self.value.fmt(f);
+        }
+        self.style.write_prefix(f)?;
+        self.value.fmt(f)?;
+        f.write_str("\x1b[0m")
+    }
+}
+
+/// The styles of each part of a rendered report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Theme {
+    /// Message of the reported error
+    pub message: Style,
+    /// Messages of its causes
+    pub cause: Style,
+    /// `at` and `Caused by:`
+    pub keyword: Style,
+    /// The displayed location
+    pub path: Style,
+    /// Line numbers and the `|` gutter of source snippets
+    pub line_number: Style,
+    /// The caret under the located column of source snippets
+    pub caret: Style,
+}
+
+impl Theme {
+    /// No styling
+    pub const fn plain() -> Self {
+        Theme {
+            message: Style::new(),
+            cause: Style::new(),
+            keyword: Style::new(),
+            path: Style::new(),
+            line_number: Style::new(),
+            caret: Style::new(),
+        }
+    }
+
+    /// Bright colours for dark terminal backgrounds, the default
+    pub const fn dark() -> Self {
+        Theme {
+            message: Style::new().fg(Color::BrightRed).bold(),
+            cause: Style::new().fg(Color::Yellow),
+            keyword: Style::new().fg(Color::BrightBlack),
+            path: Style::new().fg(Color::BrightCyan),
+            line_number: Style::new().fg(Color::BrightBlue).bold(),
+            caret: Style::new().fg(Color::BrightRed).bold(),
+        }
+    }
+
+    /// Darker colours for light terminal backgrounds
+    pub const fn light() -> Self {
+        Theme {
+            message: Style::new().fg(Color::Red).bold(),
+            cause: Style::new().fg(Color::Magenta),
+            keyword: Style::new().dimmed(),
+            path: Style::new().fg(Color::Blue).underline(),
+            line_number: Style::new().fg(Color::Blue).bold(),
+            caret: Style::new().fg(Color::Red).bold(),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+/// When output is coloured
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// When stderr is a terminal, unless `NO_COLOR` is set. `CLICOLOR_FORCE` enables colours
+    /// regardless of the terminal.
+    Auto,
+    Always,
+    #[default]
+    Never,
+}
+
+impl ColorChoice {
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Auto => auto_enabled(
+                std::env::var_os("NO_COLOR").as_deref(),
+                std::env::var_os("CLICOLOR_FORCE").as_deref(),
+                || std::io::stderr().is_terminal(),
+            ),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// `NO_COLOR` takes precedence, both are ignored when empty and `CLICOLOR_FORCE` is ignored when
+/// `0`
+fn auto_enabled(
+    no_color: Option<&std::ffi::OsStr>,
+    force: Option<&std::ffi::OsStr>,
+    is_terminal: impl FnOnce() -> bool,
+) -> bool {
+    if no_color.is_some_and(|value| !value.is_empty()) {
+        return false;
+    }
+    if force.is_some_and(|value| !value.is_empty() && value != "0") {
+        return true;
+    }
+    is_terminal()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn test_paint() {
+        assert_eq!(Style::new().paint("at").to_string(), "at");
+        assert_eq!(
+            Style::new().fg(Color::Red).bold().paint("at").to_string(),
+            "\x1b[1;31mat\x1b[0m"
+        );
+        assert_eq!(
+            Style::new()
+                .fg(Color::Fixed(208))
+                .italic()
+                .underline()
+                .paint(40)
+                .to_string(),
+            "\x1b[3;4;38;5;208m40\x1b[0m"
+        );
+        assert_eq!(
+            Style::new().dimmed().paint("at").to_string(),
+            "\x1b[2mat\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_color_choice() {
+        let set = Some(OsStr::new("1"));
+        let empty = Some(OsStr::new(""));
+        let zero = Some(OsStr::new("0"));
+        assert!(auto_enabled(None, None, || true));
+        assert!(!auto_enabled(None, None, || false));
+        assert!(!auto_enabled(set, None, || true));
+        assert!(auto_enabled(empty, None, || true));
+        assert!(auto_enabled(None, set, || false));
+        assert!(!auto_enabled(None, zero, || false));
+        assert!(!auto_enabled(set, set, || true));
+        assert!(ColorChoice::Always.enabled());
+        assert!(!ColorChoice::Never.enabled());
+    }
+}
@@ -0,0 +1,184 @@
+//! Emits a `tracing` event for each located conversion and each use of `location!`.
+//!
+//! Events are recorded at [`Level::DEBUG`] by default with the fields:
+//! - `code.filepath`, `code.lineno`, `code.column`: the captured location, with the file
+//!   remapped or hashed as when it is displayed
+//! - `error.source_type`, `error.target_type`: type names of the converted value and the
+//!   `#[derive(Locate)]` type (conversions only)
+//! - `error.message`: `Display` of the converted value, when it implements `Display`
+//!   (conversions only)
+
+use crate::{Location, paths};
+use core::fmt::Display;
+use core::sync::atomic::{AtomicU8, Ordering};
+use tracing::Level;
+
+const DISABLED: u8 = 0;
+
+static LEVEL: AtomicU8 = AtomicU8::new(encode(Some(Level::DEBUG)));
+
+const fn encode(level: Option<Level>) -> u8 {
+    match level {
+        None => DISABLED,
+        Some(Level::TRACE) => 1,
+        Some(Level::DEBUG) => 2,
+        Some(Level::INFO) => 3,
+        Some(Level::WARN) => 4,
+        Some(Level::ERROR) => 5,
+    }
+}
+
+/// Sets the level events are emitted at, or disables them with `None`
+pub fn set_level(level: Option<Level>) {
+    LEVEL.store(encode(level), Ordering::Relaxed);
+}
+
+/// The level events are emitted at, `None` if disabled
+pub fn level() -> Option<Level> {
+    match LEVEL.load(Ordering::Relaxed) {
+        1 => Some(Level::TRACE),
+        2 => Some(Level::DEBUG),
+        3 => Some(Level::INFO),
+        4 => Some(Level::WARN),
+        5 => Some(Level::ERROR),
+        _ => None,
+    }
+}
+
+// `tracing::event!` requires a constant level
+macro_rules! event_at_level {
+    ($($fields:tt)*) => {
+        match level() {
+            Some(Level::TRACE) => tracing::event!(Level::TRACE, $($fields)*),
+            Some(Level::DEBUG) => tracing::event!(Level::DEBUG, $($fields)*),
+            Some(Level::INFO) => tracing::event!(Level::INFO, $($fields)*),
+            Some(Level::WARN) => tracing::event!(Level::WARN, $($fields)*),
+            Some(Level::ERROR) => tracing::event!(Level::ERROR, $($fields)*),
+            None => {}
+        }
+    };
+}
+
+pub(crate) fn conversion(
+    location: &Location,
+    source_type: &'static str,
+    target_type: &'static str,
+    message: Option<&dyn Display>,
+) {
+    event_at_level!(
+        code.filepath = %filepath(location),
+        code.lineno = location.line(),
+        code.column = location.column(),
+        error.source_type = source_type,
+        error.target_type = target_type,
+        error.message = message.map(tracing::field::display),
+        "located conversion"
+    );
+}
+
+pub(crate) fn location(location: &Location) {
+    event_at_level!(
+        code.filepath = %filepath(location),
+        code.lineno = location.line(),
+        code.column = location.column(),
+        "location captured"
+    );
+}
+
+/// The file of `location`, remapped or hashed as when it is displayed
+fn filepath(location: &Location) -> String {
+    paths::export(location.file())
+}
+
+#[cfg(all(test, not(locate_error_strip)))]
+mod tests {
+    use super::*;
+    use crate::location;
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    type Events = Arc<Mutex<Vec<(Level, BTreeMap<String, String>)>>>;
+
+    /// Records the level and fields of each event
+    #[derive(Default)]
+    struct Recorder(Events);
+
+    struct Fields(BTreeMap<String, String>);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn core::fmt::Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{value:?}"));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields(BTreeMap::new());
+            event.record(&mut fields);
+            self.0
+                .lock()
+                .unwrap()
+                .push((*event.metadata().level(), fields.0));
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn test_events() {
+        let recorder = Recorder::default();
+        let events = recorder.0.clone();
+        tracing::subscriber::with_default(recorder, || {
+            let location = location!();
+            conversion(&location, "Source", "Target", Some(&"message"));
+            conversion(&location, "Source", "Target", None);
+
+            set_level(Some(Level::WARN));
+            assert_eq!(level(), Some(Level::WARN));
+            conversion(&location, "Source", "Target", None);
+
+            set_level(None);
+            let _ = location!();
+            conversion(&location, "Source", "Target", None);
+            set_level(Some(Level::DEBUG));
+        });
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 4);
+
+        let (level, fields) = &events[0];
+        assert_eq!(*level, Level::DEBUG);
+        assert_eq!(fields["message"], "location captured");
+        assert_eq!(fields["code.filepath"], file!());
+        assert!(!fields.contains_key("error.target_type"));
+
+        let (level, fields) = &events[1];
+        assert_eq!(*level, Level::DEBUG);
+        assert_eq!(fields["message"], "located conversion");
+        assert_eq!(fields["code.filepath"], file!());
+        assert_eq!(fields["error.source_type"], "\"Source\"");
+        assert_eq!(fields["error.target_type"], "\"Target\"");
+        assert_eq!(fields["error.message"], "message");
+
+        assert!(!events[2].1.contains_key("error.message"));
+        assert_eq!(events[3].0, Level::WARN);
+    }
+}
@@ -0,0 +1,42 @@
+//! Reads the runtime mode from `LOCATE_ERROR` in a child process for each value, as the variable
+//! is only read once
+#![cfg(not(locate_error_strip))]
+
+use locate_error_core::Location;
+use locate_error_core::runtime::{self, hash_path};
+use std::process::Command;
+
+/// Set in the child processes run by `test_env`
+const CHILD: &str = "LOCATE_ERROR_ENV_CHILD";
+
+/// Prints the mode and a displayed location to stderr when run by `test_env`
+#[test]
+fn child() {
+    if std::env::var_os(CHILD).is_some() {
+        let location = Location::from_parts("src/main.rs".to_string(), 40, 19);
+        eprintln!("{:?} {location}", runtime::mode());
+    }
+}
+
+/// Runs `child` with `LOCATE_ERROR` set to `value`, returning its stderr
+fn run(value: &str) -> String {
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(["child", "--exact", "--nocapture"])
+        .env(CHILD, "1")
+        .env("LOCATE_ERROR", value)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn test_env() {
+    assert_eq!(
+        run("redact"),
+        format!("Redact {}:40:19\n", hash_path("src/main.rs"))
+    );
+    assert_eq!(run("off"), "Off \n");
+    // Unrecognised values leave locations on
+    assert_eq!(run("sometimes"), "On src/main.rs:40:19\n");
+}
@@ -0,0 +1,178 @@
+//! Exports locations with the global path remap set, in full and redacted. Changes the global
+//! runtime mode, so runs in its own test binary to not affect the unit tests.
+#![cfg(all(
+    not(locate_error_strip),
+    any(
+        feature = "serde",
+        feature = "metrics",
+        feature = "tracing",
+        feature = "sentry"
+    )
+))]
+#![cfg_attr(
+    not(all(feature = "serde", feature = "sentry")),
+    allow(dead_code, unused_imports)
+)]
+
+use locate_error_core::paths::{self, PathRemap};
+use locate_error_core::runtime::{self, Mode, hash_path};
+use locate_error_core::{Located, Location};
+use std::error::Error;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
+
+/// Sets the remap and a mode, held for the duration of a test as tests run concurrently. Redacted
+/// files are hashed without being remapped.
+fn set_mode(mode: Mode) -> MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    let lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    paths::set_global(PathRemap::new().strip_prefix("locate-error-core/"));
+    runtime::set_mode(mode);
+    lock
+}
+
+#[derive(Debug)]
+struct NotFound {
+    location: Location,
+}
+
+impl fmt::Display for NotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not found")
+    }
+}
+
+impl Error for NotFound {}
+
+impl Located for NotFound {
+    fn location(&self) -> Option<&Location> {
+        Some(&self.location)
+    }
+
+    fn located_source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_snapshot() {
+    use locate_error_core::{Snapshot, register};
+
+    register::<NotFound>();
+    let error = NotFound {
+        location: Location::from_parts("locate-error-core/src/main.rs".to_string(), 40, 19),
+    };
+    let location = |mode| {
+        let _lock = set_mode(mode);
+        serde_json::to_value(Snapshot::new(&error)).unwrap()["hops"][0]["location"].take()
+    };
+    assert_eq!(
+        location(Mode::On),
+        serde_json::json!({ "file": "src/main.rs", "line": 40, "column": 19 })
+    );
+    assert_eq!(
+        location(Mode::Redact),
+        serde_json::json!({
+            "file": hash_path("locate-error-core/src/main.rs"),
+            "line": 40,
+            "column": 19,
+        })
+    );
+    assert!(location(Mode::Off).is_null());
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn test_metrics() {
+    use locate_error_core::__private::{self, caller};
+
+    /// Only counts into this type are checked
+    struct MetricsTarget;
+
+    let file = |mode| {
+        let _lock = set_mode(mode);
+        __private::locate::<(), MetricsTarget>(caller!(), true, None, None);
+        let prometheus = locate_error_core::metrics::snapshot().to_prometheus();
+        let line = prometheus
+            .lines()
+            .find(|line| line.contains("MetricsTarget"))
+            .unwrap()
+            .to_string();
+        line.split('"').nth(1).unwrap().to_string()
+    };
+    assert_eq!(file(Mode::On), "tests/export.rs");
+    assert_eq!(file(Mode::Redact), hash_path(file!()));
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_tracing() {
+    use locate_error_core::location;
+    use std::fmt;
+    use std::sync::Arc;
+    use tracing::field::{Field, Visit};
+    use tracing::{Event, Subscriber};
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+    /// Records the `code.filepath` field of each event
+    struct Filepaths(Arc<Mutex<Vec<String>>>);
+
+    impl Visit for Filepaths {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            if field.name() == "code.filepath" {
+                self.0.lock().unwrap().push(format!("{value:?}"));
+            }
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for Filepaths {
+        fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+            event.record(&mut Filepaths(self.0.clone()));
+        }
+    }
+
+    let filepaths = Arc::new(Mutex::new(Vec::new()));
+    let subscriber = tracing_subscriber::registry().with(Filepaths(filepaths.clone()));
+    tracing::subscriber::with_default(subscriber, || {
+        for mode in [Mode::On, Mode::Redact] {
+            let _lock = set_mode(mode);
+            let _ = location!();
+        }
+    });
+    assert_eq!(
+        *filepaths.lock().unwrap(),
+        vec!["tests/export.rs".to_string(), hash_path(file!())]
+    );
+}
+
+#[cfg(feature = "sentry")]
+#[test]
+fn test_sentry() {
+    use locate_error_core::sentry::EventBuilder;
+    use locate_error_core::{Snapshot, register};
+
+    register::<NotFound>();
+    let error = NotFound {
+        location: Location::from_parts("locate-error-core/src/main.rs".to_string(), 40, 19),
+    };
+    let filenames = |mode| {
+        let _lock = set_mode(mode);
+        let builder = EventBuilder::new();
+        [
+            builder.build(&error),
+            builder.build_snapshot(&Snapshot::new(&error)),
+        ]
+        .map(|event| {
+            event["exception"]["values"][0]["stacktrace"]["frames"][0]["filename"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        })
+    };
+    let remapped = "src/main.rs".to_string();
+    assert_eq!(filenames(Mode::On), [remapped.clone(), remapped]);
+    // Hashed once, from the captured file
+    let hashed = hash_path("locate-error-core/src/main.rs");
+    assert_eq!(filenames(Mode::Redact), [hashed.clone(), hashed]);
+}
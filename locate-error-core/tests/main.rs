@@ -0,0 +1,43 @@
+//! Installs the panic hook of `#[locate_error::main]`, so runs in its own test binary to not
+//! affect the output of the unit tests
+
+use locate_error_core::__private::run_main;
+use std::fmt;
+use std::process::ExitCode;
+
+#[derive(Debug)]
+struct Failed;
+
+impl fmt::Display for Failed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed")
+    }
+}
+
+impl std::error::Error for Failed {}
+
+#[test]
+fn test_run_main() {
+    // `ExitCode` only implements `Debug` for comparison
+    let mut printed = None;
+    let exit_code = run_main(
+        || Err::<(), _>(Failed),
+        3,
+        |error| printed = Some(error.to_string()),
+    );
+    assert_eq!(format!("{exit_code:?}"), format!("{:?}", ExitCode::from(3)));
+    assert_eq!(printed.as_deref(), Some("failed"));
+
+    let exit_code = run_main(
+        || Ok::<_, Failed>(ExitCode::from(4)),
+        3,
+        |_| panic!("not an error"),
+    );
+    assert_eq!(format!("{exit_code:?}"), format!("{:?}", ExitCode::from(4)));
+
+    let exit_code = run_main(|| Ok::<(), Failed>(()), 3, |_| panic!("not an error"));
+    assert_eq!(format!("{exit_code:?}"), format!("{:?}", ExitCode::SUCCESS));
+
+    // Panics are still caught after the hook is installed
+    assert!(std::panic::catch_unwind(|| panic!("caught")).is_err());
+}
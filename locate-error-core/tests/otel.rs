@@ -0,0 +1,81 @@
+//! Exports the attributes through the OpenTelemetry SDK. Sets the global path remap, so runs in
+//! its own test binary to not affect the unit tests
+#![cfg(not(locate_error_strip))]
+
+use locate_error_core::otel::{self, Attribute, Value};
+use locate_error_core::paths::{self, PathRemap};
+use locate_error_core::{Located, Location, register};
+use opentelemetry::trace::{Span, Tracer, TracerProvider};
+use opentelemetry::{Key, KeyValue};
+use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug)]
+struct NotFound {
+    location: Location,
+}
+
+impl fmt::Display for NotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not found")
+    }
+}
+
+impl Error for NotFound {}
+
+impl Located for NotFound {
+    fn location(&self) -> Option<&Location> {
+        Some(&self.location)
+    }
+
+    fn located_source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+fn key_value(attribute: Attribute) -> KeyValue {
+    match attribute.value {
+        Value::String(value) => KeyValue::new(attribute.key, value),
+        Value::Int(value) => KeyValue::new(attribute.key, value),
+    }
+}
+
+#[test]
+fn test_in_memory_exporter() {
+    register::<NotFound>();
+    paths::set_global(PathRemap::new().strip_prefix("/build/"));
+    let error = NotFound {
+        location: Location::from_parts("/build/src/main.rs".to_string(), 40, 19),
+    };
+
+    let exporter = InMemorySpanExporter::default();
+    let provider = SdkTracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    let mut span = provider.tracer("test").start("request");
+    let attributes = otel::exception_attributes(&error);
+    span.add_event("exception", attributes.into_iter().map(key_value).collect());
+    span.end();
+    provider.force_flush().unwrap();
+
+    let spans = exporter.get_finished_spans().unwrap();
+    let event = &spans[0].events[0];
+    assert_eq!(event.name, "exception");
+    let attribute = |key: &'static str| {
+        event
+            .attributes
+            .iter()
+            .find(|attribute| attribute.key == Key::from_static_str(key))
+            .map(|attribute| attribute.value.to_string())
+    };
+    assert_eq!(attribute("exception.message").unwrap(), "not found");
+    assert_eq!(
+        attribute("exception.stacktrace").unwrap(),
+        "not found\n    at src/main.rs:40:19"
+    );
+    // Remapped as when displayed
+    assert_eq!(attribute("code.filepath").unwrap(), "src/main.rs");
+    assert_eq!(attribute("code.lineno").unwrap(), "40");
+    assert_eq!(attribute("code.column").unwrap(), "19");
+}
@@ -0,0 +1,38 @@
+//! Replaces the process wide panic hook, so runs in its own test binary to not affect the output
+//! of the unit tests
+#![cfg(not(locate_error_strip))]
+
+use locate_error_core::Located;
+use locate_error_core::panic::catch_unwind_located;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[test]
+fn test_scoped_hook() {
+    let printed = Arc::new(AtomicUsize::new(0));
+    let counted = printed.clone();
+    std::panic::set_hook(Box::new(move |_| {
+        counted.fetch_add(1, Ordering::Relaxed);
+    }));
+
+    // Caught panics are returned instead of reaching the previous hook
+    let error = catch_unwind_located(|| panic!("caught")).unwrap_err();
+    assert!(error.location().is_some());
+    assert_eq!(printed.load(Ordering::Relaxed), 0);
+
+    // Panics on other threads are passed on while the hook is set
+    catch_unwind_located(|| {
+        assert!(
+            std::thread::spawn(|| panic!("other thread"))
+                .join()
+                .is_err()
+        );
+    })
+    .unwrap();
+    assert_eq!(printed.load(Ordering::Relaxed), 1);
+
+    // The previous hook is restored afterwards
+    assert!(std::panic::catch_unwind(|| panic!("after")).is_err());
+    assert_eq!(printed.load(Ordering::Relaxed), 2);
+    let _ = std::panic::take_hook();
+}
@@ -0,0 +1,89 @@
+//! Changes the global runtime mode and hyperlink scheme, so runs in its own test binary to not affect the unit tests
+#![cfg(not(locate_error_strip))]
+
+use locate_error_core::__private::{self, caller};
+use locate_error_core::hyperlink::{self, Hyperlinks, UrlScheme};
+use locate_error_core::render::Renderer;
+use locate_error_core::runtime::{self, Mode};
+use locate_error_core::{Located, Location, location, register, set_hook};
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(Debug)]
+struct NotFound {
+    location: Location,
+}
+
+impl fmt::Display for NotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not found")
+    }
+}
+
+impl Error for NotFound {}
+
+impl Located for NotFound {
+    fn location(&self) -> Option<&Location> {
+        Some(&self.location)
+    }
+
+    fn located_source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+#[test]
+fn test_modes() {
+    let location = Location::from_parts("src/main.rs".to_string(), 40, 19);
+    assert_eq!(location.to_string(), "src/main.rs:40:19");
+
+    runtime::set_mode(Mode::Redact);
+    assert_eq!(
+        location.to_string(),
+        format!("{}:40:19", runtime::hash_path("src/main.rs"))
+    );
+    assert_eq!(location!().file(), file!());
+
+    runtime::set_mode(Mode::Off);
+    assert_eq!(location.to_string(), "");
+    let captured = location!();
+    assert_eq!(
+        (captured.file(), captured.line(), captured.column()),
+        ("", 0, 0)
+    );
+
+    // Reports leave out the location line
+    register::<NotFound>();
+    let error = NotFound {
+        location: location.clone(),
+    };
+    assert_eq!(Renderer::new().render(&error).to_string(), "not found");
+
+    // Conversions are neither reported to the hook nor counted
+    let events = Arc::new(AtomicUsize::new(0));
+    let counted = events.clone();
+    set_hook(move |_| {
+        counted.fetch_add(1, Ordering::Relaxed);
+    });
+    __private::locate::<(), NotFound>(caller!(), true, None, None);
+    assert_eq!(events.load(Ordering::Relaxed), 0);
+    #[cfg(feature = "metrics")]
+    assert!(locate_error_core::metrics::snapshot().sites.is_empty());
+
+    runtime::set_mode(Mode::On);
+    assert_eq!(location.to_string(), "src/main.rs:40:19");
+    __private::locate::<(), NotFound>(caller!(), true, None, None);
+    assert_eq!(events.load(Ordering::Relaxed), 1);
+
+    // The global hyperlink scheme only applies to reports, not a location written with `Display`
+    hyperlink::set_global(Some(UrlScheme::File));
+    assert_eq!(location.to_string(), "src/main.rs:40:19");
+    let rendered = Renderer::new()
+        .hyperlinks(Hyperlinks::Always)
+        .render(&error)
+        .to_string();
+    hyperlink::set_global(None);
+    assert!(rendered.contains("\x1b]8;;file:///"));
+}
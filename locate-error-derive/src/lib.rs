@@ -2,12 +2,15 @@ extern crate proc_macro;
 use proc_macro::TokenStream;
 use quote::{quote, quote_spanned};
 use syn::{
-    Attribute, Data, DataEnum, DataStruct, DeriveInput, Fields, Generics, Ident, Type,
-    parse_macro_input, parse_quote, spanned::Spanned,
+    Attribute, Data, DataEnum, DataStruct, DeriveInput, Fields, Generics, Ident, Index, ItemFn,
+    LitInt, Member, ReturnType, Type, parse_macro_input, parse_quote, spanned::Spanned,
 };
 
 /// This macro is used to implement `From` on an enum or struct and locating
 /// where the `From` impl is called. Typically used for tracking sources of bubbling errors with `thiserror`.
+///
+/// Also implements `locate_error::Located`, exposing the captured location and the
+/// `#[locate_from]` value.
 #[proc_macro_derive(Locate, attributes(locate_from))]
 pub fn locate(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
@@ -33,13 +36,78 @@ pub fn locate(input: TokenStream) -> TokenStream {
     }
 }
 
+/// Reports the errors and panics of `main` with their locations.
+///
+/// Installs the panic hook `locate_error::panic::install_hook`. When `main` returns a `Result`, an
+/// error is printed with its causes and locations, and the process exits with the code given as
+/// `#[locate_error::main(exit_code = 2)]`, or 1 by default. Errors implementing `Error`, and boxed
+/// `dyn Error`, are converted into a report as by `locate_error::Report`, located at the return
+/// type of `main` unless they carry their own location, and rendered as by
+/// `locate_error::render::Renderer`; other errors are printed with `Debug`.
+#[proc_macro_attribute]
+pub fn main(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut exit_code: u8 = 1;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("exit_code") {
+            exit_code = meta.value()?.parse::<LitInt>()?.base10_parse()?;
+            Ok(())
+        } else {
+            Err(meta.error("expected `exit_code = <u8>`"))
+        }
+    });
+    parse_macro_input!(args with parser);
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = parse_macro_input!(input as ItemFn);
+
+    if let Some(asyncness) = sig.asyncness {
+        return TokenStream::from(quote_spanned! {
+            asyncness.span() => compile_error!("#[locate_error::main] does not support `async fn`, apply it to a synchronous `main` which starts the runtime");
+        });
+    }
+    if !sig.inputs.is_empty() {
+        return TokenStream::from(quote_spanned! {
+            sig.inputs.span() => compile_error!("#[locate_error::main] requires `main` to take no arguments");
+        });
+    }
+
+    match &sig.output {
+        ReturnType::Type(_, ty) if is_result_type(ty) => {
+            let mut outer_sig = sig.clone();
+            outer_sig.output = parse_quote!(-> ::std::process::ExitCode);
+            // Errors are located where `main` returns them, at its return type
+            let caller = quote_spanned!(ty.span() => ::locate_error::__private::caller!());
+            TokenStream::from(quote! {
+                #(#attrs)*
+                #vis #outer_sig {
+                    ::locate_error::__private::run_main(move || -> #ty #block, #exit_code, |error| {
+                        #[allow(unused_imports)]
+                        use ::locate_error::__private::{PrintBoxedError as _, PrintDebugError as _, PrintError as _};
+                        (&&&::locate_error::__private::MainError::new(error)).print_error(#caller);
+                    })
+                }
+            })
+        }
+        _ => TokenStream::from(quote! {
+            #(#attrs)*
+            #vis #sig {
+                ::locate_error::panic::install_hook();
+                #block
+            }
+        }),
+    }
+}
+
 fn process_enum(
     data: &DataEnum,
     from_attributes: &[Attribute],
     generics: &Generics,
     ident: &Ident,
 ) -> TokenStream {
-    let mut from_impls = vec![];
+    let mut sites = vec![];
     let mut n_has_locate_from = 0;
     for variant in &data.variants {
         let variant_name = &variant.ident;
@@ -47,41 +115,27 @@ fn process_enum(
 
         match &fields {
             Fields::Unnamed(fields) => {
-                for field in fields.unnamed.iter() {
-                    if let Some(index) = locate_from_attr_index(&field.attrs) {
+                for (position, field) in fields.unnamed.iter().enumerate() {
+                    if locate_from_attr_index(&field.attrs).is_some() {
                         if fields.unnamed.len() != 2 {
                             return TokenStream::from(quote_spanned! {
                                 variant.ident.span() => compile_error!("Locate requires enums variants with the #[locate_from] attribute to have exactly two fields, one for the source and one for the location");
                             });
                         }
-                        if let Some(other_field) = fields.unnamed.iter().nth((index + 1) % 2) {
-                            if !is_location_type(&other_field.ty) {
-                                return TokenStream::from(quote_spanned! {
-                                    other_field.ident.span() => compile_error!("Variants with #[locate_from] must have a field of type `locate_from::Location`");
-                                });
-                            }
-                        }
-                        n_has_locate_from += 1;
-                        if let Type::Path(path) = &field.ty {
-                            let field_type = &path.path;
-                            from_impls.push(quote! {
-                                #(#from_attributes)*
-                                impl #generics ::core::convert::From<#field_type> for #ident #generics {
-                                    #[track_caller]
-                                    fn from(value: #field_type) -> Self {
-                                        let location = ::std::panic::Location::caller();
-                                        #ident::#variant_name {
-                                            0: value,
-                                            1: ::locate_error::Location {
-                                                file: location.file().to_string(),
-                                                line: location.line(),
-                                                column: location.column(),
-                                            }
-                                        }
-                                    }
-                                }
+                        if let Some(other_field) = fields.unnamed.iter().nth(1 - position)
+                            && !is_location_type(&other_field.ty)
+                        {
+                            return TokenStream::from(quote_spanned! {
+                                other_field.ident.span() => compile_error!("Variants with #[locate_from] must have a field of type `locate_from::Location`");
                             });
                         }
+                        n_has_locate_from += 1;
+                        sites.push(LocatedFields {
+                            variant: Some(variant_name),
+                            source: Member::Unnamed(Index::from(position)),
+                            source_ty: &field.ty,
+                            location: Member::Unnamed(Index::from(1 - position)),
+                        });
                     }
                 }
             }
@@ -108,26 +162,12 @@ fn process_enum(
                         }
 
                         n_has_locate_from += 1;
-                        if let Type::Path(path) = &field.ty {
-                            let field_type = &path.path;
-                            from_impls.push(quote! {
-                                #(#from_attributes)*
-                                impl #generics ::core::convert::From<#field_type> for #ident #generics {
-                                    #[track_caller]
-                                    fn from(value: #field_type) -> Self {
-                                        let location = ::std::panic::Location::caller();
-                                        #ident::#variant_name {
-                                            #field_name:value,
-                                            location: ::locate_error::Location {
-                                                file: location.file().to_string(),
-                                                line: location.line(),
-                                                column: location.column(),
-                                            }
-                                        }
-                                    }
-                                }
-                            });
-                        }
+                        sites.push(LocatedFields {
+                            variant: Some(variant_name),
+                            source: Member::Named(field_name.clone()),
+                            source_ty: &field.ty,
+                            location: Member::Named(Ident::new("location", field_name.span())),
+                        });
                     }
                 }
             }
@@ -141,11 +181,7 @@ fn process_enum(
         });
     }
 
-    let expanded = quote! {
-        #(#from_impls)*
-    };
-
-    TokenStream::from(expanded)
+    TokenStream::from(located_impls(&sites, from_attributes, generics, ident))
 }
 
 fn process_struct(
@@ -154,7 +190,6 @@ fn process_struct(
     generics: &Generics,
     ident: &Ident,
 ) -> TokenStream {
-    let mut from_impl: proc_macro2::TokenStream = quote! {};
     // Find fields with locate_from attribute
     let locate_from_fields: Vec<_> = data
         .fields
@@ -195,28 +230,115 @@ fn process_struct(
     let field = locate_from_fields.first().unwrap();
     let field_name = field.ident.as_ref().unwrap();
 
-    if let Type::Path(path) = &field.ty {
-        let field_type = &path.path;
-        from_impl = quote! {
+    let site = LocatedFields {
+        variant: None,
+        source: Member::Named(field_name.clone()),
+        source_ty: &field.ty,
+        location: Member::Named(Ident::new("location", field_name.span())),
+    };
+    TokenStream::from(located_impls(&[site], from_attributes, generics, ident))
+}
+
+/// A `#[locate_from]` field and its `Location` field, in a struct or an enum variant
+struct LocatedFields<'a> {
+    variant: Option<&'a Ident>,
+    source: Member,
+    source_ty: &'a Type,
+    location: Member,
+}
+
+/// Generates a `From` impl for each `#[locate_from]` field and a `Located` impl for the type
+fn located_impls(
+    sites: &[LocatedFields],
+    from_attributes: &[Attribute],
+    generics: &Generics,
+    ident: &Ident,
+) -> proc_macro2::TokenStream {
+    let paths: Vec<_> = sites
+        .iter()
+        .map(|site| match site.variant {
+            Some(variant_name) => quote!(#ident::#variant_name),
+            None => quote!(#ident),
+        })
+        .collect();
+    let sources: Vec<_> = sites.iter().map(|site| &site.source).collect();
+    let locations: Vec<_> = sites.iter().map(|site| &site.location).collect();
+
+    // Registration takes a global lock, so is skipped after the first conversion. Statics in
+    // generic impls are shared by every instantiation, so generic types register every time.
+    let registered = || match generics.params.is_empty() {
+        true => quote! {
+            ::core::option::Option::Some({
+                static REGISTERED: ::core::sync::atomic::AtomicBool =
+                    ::core::sync::atomic::AtomicBool::new(false);
+                &REGISTERED
+            })
+        },
+        false => quote!(::core::option::Option::None),
+    };
+
+    let from_impls = sites.iter().zip(&paths).map(|(site, path)| {
+        let LocatedFields {
+            source,
+            source_ty,
+            location: location_field,
+            ..
+        } = site;
+        let (registered, registered_source) = (registered(), registered());
+        quote! {
             #(#from_attributes)*
-            impl #generics ::core::convert::From<#field_type> for #ident #generics {
-                #[track_caller]
-                fn from(value: #field_type) -> Self {
-                    let location = ::std::panic::Location::caller();
-                    #ident {
-                        #field_name: value,
-                        location: ::locate_error::Location {
-                            file: location.file().to_string(),
-                            line: location.line(),
-                            column: location.column(),
+            impl #generics ::core::convert::From<#source_ty> for #ident #generics {
+                // Only `#[track_caller]` when locations are not stripped
+                ::locate_error::__private::track_caller! {
+                    fn from(value: #source_ty) -> Self {
+                        use ::locate_error::__private::{
+                            DisplayMessage as _, ErrorSource as _, LocatedSource as _,
+                            NoDisplayMessage as _, NoErrorSource as _, NoRegisterError as _,
+                            NotLocatedSource as _, RegisterError as _,
+                        };
+                        (&::locate_error::__private::Register::<Self>::new()).register(#registered);
+                        // Lets generic located sources be recognised once converted into a
+                        // non-generic type, where their concrete type is known
+                        (&::locate_error::__private::Register::<#source_ty>::new()).register(#registered_source);
+                        let location = ::locate_error::__private::locate::<#source_ty, Self>(
+                            ::locate_error::__private::caller!(),
+                            !(&::locate_error::__private::Source(&value)).is_located(),
+                            (&::locate_error::__private::Message(&value)).message(),
+                            (&::locate_error::__private::Source(&value)).located_source(),
+                        );
+                        #path {
+                            #source: value,
+                            #location_field: location,
                         }
                     }
                 }
             }
-        };
-    }
+        }
+    });
+
+    quote! {
+        #(#from_impls)*
 
-    TokenStream::from(from_impl)
+        #(#from_attributes)*
+        impl #generics ::locate_error::Located for #ident #generics {
+            #[allow(unreachable_patterns)]
+            fn location(&self) -> ::core::option::Option<&::locate_error::Location> {
+                match self {
+                    #(#paths { #locations: location, .. } => ::core::option::Option::Some(location),)*
+                    _ => ::core::option::Option::None,
+                }
+            }
+
+            #[allow(unreachable_patterns)]
+            fn located_source(&self) -> ::core::option::Option<&(dyn ::core::error::Error + 'static)> {
+                use ::locate_error::__private::{ErrorSource as _, NoErrorSource as _};
+                match self {
+                    #(#paths { #sources: source, .. } => (&::locate_error::__private::Source(source)).located_source(),)*
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+    }
 }
 
 fn locate_from_attr_index(attributes: &[Attribute]) -> Option<usize> {
@@ -231,18 +353,26 @@ fn locate_from_attr_index(attributes: &[Attribute]) -> Option<usize> {
 
 // Helper function to check if a type is Location (may not identify full path correctly, but works in most cases)
 fn is_location_type(ty: &Type) -> bool {
-    if let Type::Path(type_path) = ty {
-        if let Some(last_segment) = type_path.path.segments.last() {
-            // Check if the last segment is "Location"
-            if last_segment.ident == "Location" {
-                // Simplistic check, verify the last segment
-                return true;
-            }
-        }
+    if let Type::Path(type_path) = ty
+        && let Some(last_segment) = type_path.path.segments.last()
+        // Check if the last segment is "Location"
+        && last_segment.ident == "Location"
+    {
+        // Simplistic check, verify the last segment
+        return true;
     }
     false
 }
 
+// Checks the last segment only, as with `is_location_type`
+fn is_result_type(ty: &Type) -> bool {
+    matches!(ty, Type::Path(type_path) if type_path
+        .path
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "Result"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -275,4 +405,12 @@ mod tests {
         let attributes = vec![attr1, attr2];
         assert!(locate_from_attr_index(&attributes).is_none());
     }
+
+    #[test]
+    fn test_is_result_type() {
+        assert!(is_result_type(&parse_quote!(Result<(), AppError>)));
+        assert!(is_result_type(&parse_quote!(std::io::Result<()>)));
+        assert!(!is_result_type(&parse_quote!(ExitCode)));
+        assert!(!is_result_type(&parse_quote!(())));
+    }
 }
@@ -0,0 +1,61 @@
+//! Tests every generated `From` impl reports its conversion to the hook.
+
+use locate_error::Locate;
+use locate_error::Location;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+#[derive(Error, Debug, Locate)]
+#[error("outer")]
+pub struct OuterError {
+    #[locate_from]
+    inner_error: InnerError,
+    location: Location,
+}
+
+#[derive(Error, Debug)]
+#[error("inner")]
+pub struct InnerError;
+
+#[derive(Debug, Locate)]
+pub struct NotAnError {
+    #[locate_from]
+    code: u32,
+    location: Location,
+}
+
+fn main() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorded = events.clone();
+    locate_error::set_hook(move |event| {
+        recorded.lock().unwrap().push((
+            event.location().clone(),
+            event.source_type(),
+            event.target_type(),
+            event.is_first_hop(),
+            event.error().map(ToString::to_string),
+            event.message().map(ToString::to_string),
+        ));
+    });
+
+    let line = line!();
+    let outer: OuterError = InnerError.into();
+    let _: NotAnError = 404.into();
+    locate_error::remove_hook();
+    let _: OuterError = InnerError.into();
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].0, outer.location);
+    assert_eq!(events[0].0.line, line + 1);
+    assert!(events[0].1.ends_with("InnerError"));
+    assert!(events[0].2.ends_with("OuterError"));
+    assert!(events[0].3);
+    assert_eq!(events[0].4.as_deref(), Some("inner"));
+    assert_eq!(events[0].5.as_deref(), Some("inner"));
+
+    assert_eq!(events[1].1, "u32");
+    assert!(events[1].2.ends_with("NotAnError"));
+    assert_eq!(events[1].4, None);
+    assert_eq!(events[1].5.as_deref(), Some("404"));
+}
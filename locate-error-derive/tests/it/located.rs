@@ -0,0 +1,109 @@
+//! Tests the `Located` impl generated by `Locate` and walking located chains.
+
+use locate_error::Chain;
+use locate_error::Locate;
+use locate_error::Located;
+use locate_error::Location;
+use thiserror::Error;
+
+#[derive(Error, Debug, Locate)]
+pub enum OuterError {
+    #[error("outer")]
+    Middle(#[locate_from] MiddleError, Location),
+
+    #[error("unlocated")]
+    Unlocated,
+}
+
+#[derive(Error, Debug, Locate)]
+#[error("middle")]
+pub struct MiddleError {
+    #[locate_from]
+    inner_error: InnerError,
+    location: Location,
+}
+
+#[derive(Error, Debug)]
+#[error("inner")]
+pub struct InnerError;
+
+#[derive(Error, Debug, Locate)]
+#[error("generic")]
+pub struct GenericError<T> {
+    // Generic `#[locate_from]` values are only followed as the `Error::source`
+    #[locate_from]
+    #[source]
+    inner_error: T,
+    location: Location,
+}
+
+#[derive(Error, Debug, Locate)]
+#[error("wrapper")]
+pub struct WrapperError {
+    #[locate_from]
+    generic: GenericError<std::io::Error>,
+    location: Location,
+}
+
+#[derive(Error, Debug, Locate)]
+#[error("other wrapper")]
+pub struct OtherWrapperError {
+    #[locate_from]
+    generic: GenericError<InnerError>,
+    location: Location,
+}
+
+#[derive(Locate)]
+pub enum NotAnError {
+    // Location first to check field positions are respected
+    Reversed(Location, #[locate_from] String),
+}
+
+fn main() {
+    let line = line!();
+    let middle: MiddleError = InnerError.into();
+    let outer: OuterError = middle.into();
+
+    assert_eq!(outer.location().unwrap().line, line + 2);
+    assert!(outer.located_source().unwrap().to_string().contains("middle"));
+    assert!(OuterError::Unlocated.location().is_none());
+    assert!(OuterError::Unlocated.located_source().is_none());
+
+    let hops: Vec<_> = Chain::new(&outer).collect();
+    assert_eq!(hops.len(), 3);
+    assert_eq!(hops[0].error().to_string(), "outer");
+    assert_eq!(hops[0].location().unwrap().line, line + 2);
+    assert!(hops[0].type_name().unwrap().ends_with("OuterError"));
+    assert_eq!(hops[1].error().to_string(), "middle");
+    assert_eq!(hops[1].location().unwrap().line, line + 1);
+    assert_eq!(hops[2].error().to_string(), "inner");
+    assert!(hops[2].location().is_none());
+
+    let line = line!();
+    let not_an_error: NotAnError = "message".to_string().into();
+    let NotAnError::Reversed(location, message) = &not_an_error;
+    assert_eq!(message, "message");
+    assert_eq!(location.line, line + 1);
+    assert_eq!(not_an_error.location().unwrap().line, line + 1);
+    // `String` is not an `Error`, so there is no source to follow
+    assert!(not_an_error.located_source().is_none());
+
+    // Still recognised after the first conversion skips registering
+    let middle: MiddleError = InnerError.into();
+    assert_eq!(Chain::new(&middle).count(), 2);
+    assert!(Chain::new(&middle).next().unwrap().location().is_some());
+
+    // Generic types are recognised once converted into a non-generic located type
+    let generic: GenericError<std::io::Error> = std::io::Error::other("io").into();
+    assert!(Chain::new(&generic).next().unwrap().location().is_none());
+    let wrapper: WrapperError = generic.into();
+    let hops: Vec<_> = Chain::new(&wrapper).collect();
+    assert_eq!(hops.len(), 3);
+    assert!(hops[1].location().is_some());
+
+    // Or after registering them
+    let generic: GenericError<InnerError> = InnerError.into();
+    locate_error::register::<GenericError<InnerError>>();
+    assert!(Chain::new(&generic).next().unwrap().location().is_some());
+    let _: OtherWrapperError = generic.into();
+}
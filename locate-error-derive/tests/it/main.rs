@@ -0,0 +1,23 @@
+//! Tests `#[locate_error::main]` accepts `main` returning a `Result` or nothing.
+
+use locate_error::{Locate, Location};
+use thiserror::Error;
+
+#[derive(Error, Debug, Locate)]
+#[error("failed to start")]
+pub struct AppError {
+    #[locate_from]
+    inner_error: std::io::Error,
+    location: Location,
+}
+
+mod unit {
+    #[locate_error::main]
+    pub fn main() {}
+}
+
+#[locate_error::main(exit_code = 3)]
+fn main() -> Result<(), AppError> {
+    unit::main();
+    Ok(())
+}
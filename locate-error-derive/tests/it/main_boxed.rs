@@ -0,0 +1,25 @@
+//! Tests `#[locate_error::main]` accepts `main` returning boxed errors.
+
+use std::error::Error;
+
+mod send_sync {
+    #[locate_error::main]
+    pub fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Ok(())
+    }
+}
+
+mod debug {
+    #[locate_error::main]
+    pub fn main() -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[locate_error::main]
+fn main() -> Result<(), Box<dyn Error>> {
+    send_sync::main();
+    debug::main();
+    "1".parse::<u8>()?;
+    Ok(())
+}
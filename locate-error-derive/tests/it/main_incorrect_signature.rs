@@ -0,0 +1,18 @@
+mod exit_code {
+    #[locate_error::main(exit_code = 256)]
+    pub fn main() -> Result<(), std::io::Error> {
+        Ok(())
+    }
+}
+
+mod arguments {
+    #[locate_error::main]
+    pub fn main(_: u8) {}
+}
+
+mod asynchronous {
+    #[locate_error::main]
+    pub async fn main() {}
+}
+
+fn main() {}
@@ -0,0 +1,58 @@
+//! Tests `Report` renders a derived error chain with every location, as when returned from `main`.
+
+use locate_error::{Locate, Location, Report};
+use thiserror::Error;
+
+#[derive(Error, Debug, Locate)]
+pub enum AppError {
+    #[error("failed to load config")]
+    Config {
+        #[locate_from]
+        source: ConfigError,
+        location: Location,
+    },
+}
+
+#[derive(Error, Debug, Locate)]
+#[error("invalid config")]
+pub struct ConfigError {
+    #[locate_from]
+    inner_error: ParseError,
+    location: Location,
+}
+
+#[derive(Error, Debug)]
+#[error("expected `=`")]
+pub struct ParseError;
+
+fn parse() -> Result<(), ConfigError> {
+    Err(ParseError)?
+}
+
+fn load() -> Result<(), AppError> {
+    parse()?;
+    Ok(())
+}
+
+fn run() -> Result<(), Report> {
+    load()?;
+    Ok(())
+}
+
+fn main() {
+    let line = line!();
+    let report = run().unwrap_err();
+    assert_eq!(report.location().line(), line - 5);
+    assert_eq!(report.to_string(), "failed to load config");
+    assert_eq!(
+        format!("{report:?}"),
+        format!(
+            "failed to load config\n    at {file}:{}:5\n\nCaused by:\n   \
+             0: invalid config\n      at {file}:{}:5\n   \
+             1: expected `=`",
+            line - 10,
+            line - 14,
+            file = file!()
+        )
+    );
+}
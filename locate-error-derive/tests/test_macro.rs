@@ -23,3 +23,29 @@ fn thiserror_integration() {
     let t = trybuild::TestCases::new();
     t.pass("tests/it/thiserror.rs");
 }
+
+#[test]
+fn located_chains() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/it/located.rs");
+}
+
+#[test]
+fn conversion_hook() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/it/hook.rs");
+}
+
+#[test]
+fn main_report() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/it/report.rs");
+}
+
+#[test]
+fn main_attribute() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/it/main.rs");
+    t.pass("tests/it/main_boxed.rs");
+    t.compile_fail("tests/it/main_incorrect_signature.rs");
+}
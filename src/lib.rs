@@ -99,6 +99,31 @@
 //! `#[error(transparent)]` do not work, so a display message must be provided.
 
 // Enum variants or structs that use the `#[locate_from]` attribute must also include a field of type `Location` which will be automatically populated with the location where the `From` trait is called. Since an additional field is added, `thiserror` attributes such as `#[error(transparent)]` do not work, so a display message must be provided.
+pub use locate_error_core::DisplayLocation;
 pub use locate_error_core::Location;
+pub use locate_error_core::ParseLocationError;
 pub use locate_error_core::location;
-pub use locate_error_derive::Locate;
+pub use locate_error_core::paths;
+pub use locate_error_core::permalinks;
+pub use locate_error_core::{
+    Chain, ConversionEvent, Hop, Located, Report, Snapshot, SnapshotHop, as_located, register,
+    remove_hook, set_hook,
+};
+pub use locate_error_core::{
+    fingerprint, format, hyperlink, otel, panic, permalink, render, runtime, snippet, style,
+};
+pub use locate_error_derive::{Locate, main};
+
+#[cfg(feature = "eyre")]
+pub use locate_error_core::eyre;
+#[cfg(feature = "metrics")]
+pub use locate_error_core::metrics;
+#[cfg(feature = "miette")]
+pub use locate_error_core::miette;
+#[cfg(feature = "sentry")]
+pub use locate_error_core::sentry;
+#[cfg(feature = "tracing")]
+pub use locate_error_core::tracing;
+
+#[doc(hidden)]
+pub use locate_error_core::__private;
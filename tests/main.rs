@@ -0,0 +1,91 @@
+//! Runs `main` of this test binary, which uses `#[locate_error::main]`, in a child process for each
+//! case, checking what is printed to stderr and the exit code
+
+use locate_error::{Locate, Location};
+use std::error::Error;
+use std::process::Command;
+use thiserror::Error;
+
+#[derive(Error, Debug, Locate)]
+#[error("failed to start")]
+pub struct AppError {
+    #[locate_from]
+    inner_error: std::num::ParseIntError,
+    location: Location,
+}
+
+/// Selects the case run by a child process
+const CASE: &str = "LOCATE_ERROR_MAIN_CASE";
+
+/// Where `main` returns errors without their own location
+const MAIN_LINE: u32 = line!() + 5;
+const ERROR_LINE: u32 = line!() + 6;
+const PANIC_LINE: u32 = line!() + 7;
+
+#[locate_error::main(exit_code = 3)]
+fn main() -> Result<(), Box<dyn Error>> {
+    match std::env::var(CASE).as_deref() {
+        Ok("error") => Err(AppError::from("x".parse::<u8>().unwrap_err()))?,
+        Ok("unlocated") => Err("x".parse::<u8>().unwrap_err())?,
+        Ok("panic") => panic!("failed to read {}", "config.toml"),
+        _ => run_cases(),
+    }
+    Ok(())
+}
+
+/// Runs a case, returning its exit code and stderr
+fn run(case: &str) -> (Option<i32>, String) {
+    let output = Command::new(std::env::current_exe().unwrap())
+        .env(CASE, case)
+        .env("RUST_BACKTRACE", "0")
+        .output()
+        .unwrap();
+    (
+        output.status.code(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+/// The displayed location of the line in this file
+fn at(line: u32, column: u32) -> String {
+    Location::from_parts(file!().to_string(), line, column).to_string()
+}
+
+fn run_cases() {
+    let (code, stderr) = run("error");
+    assert_eq!(code, Some(3));
+    // Followed by any traces captured on the first hop
+    assert!(
+        stderr.starts_with(&format!(
+            "Error: failed to start\n    at {}\n",
+            at(ERROR_LINE, 28)
+        )),
+        "{stderr}"
+    );
+    assert!(
+        stderr.ends_with("\n\nCaused by:\n   0: invalid digit found in string\n"),
+        "{stderr}"
+    );
+
+    // Located where `main` returned it
+    let (code, stderr) = run("unlocated");
+    assert_eq!(code, Some(3));
+    assert!(
+        stderr.starts_with(&format!(
+            "Error: invalid digit found in string\n    at {}",
+            at(MAIN_LINE, 14)
+        )),
+        "{stderr}"
+    );
+
+    let (code, stderr) = run("panic");
+    assert_eq!(code, Some(101));
+    assert_eq!(
+        stderr,
+        format!(
+            "thread 'main' panicked: failed to read config.toml\n    at {}\n",
+            at(PANIC_LINE, 24)
+        )
+    );
+    println!("main: ok");
+}
@@ -0,0 +1,38 @@
+//! Checks builds with `RUSTFLAGS="--cfg locate_error_strip"`, which the derive crate's trybuild
+//! tests can't cover as trybuild compiles without the caller's `RUSTFLAGS`
+#![cfg(locate_error_strip)]
+
+use locate_error::{Chain, Locate, Located, Location, location};
+use thiserror::Error;
+
+#[derive(Error, Debug, Locate)]
+pub enum OuterError {
+    #[error("outer")]
+    Inner(#[locate_from] InnerError, Location),
+
+    #[error("unlocated")]
+    Unlocated,
+}
+
+#[derive(Error, Debug)]
+#[error("inner")]
+pub struct InnerError;
+
+#[test]
+fn test_stripped() {
+    assert_eq!(size_of::<Location>(), 0);
+
+    let location = location!();
+    assert_eq!(location.to_string(), "<stripped>");
+    assert_eq!(
+        (location.file(), location.line(), location.column()),
+        ("", 0, 0)
+    );
+
+    let outer: OuterError = InnerError.into();
+    let hops: Vec<_> = Chain::new(&outer).collect();
+    assert_eq!(hops.len(), 2);
+    assert_eq!(hops[0].error().to_string(), "outer");
+    assert_eq!(hops[0].location().unwrap().to_string(), "<stripped>");
+    assert!(OuterError::Unlocated.location().is_none());
+}